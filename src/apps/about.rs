@@ -0,0 +1,50 @@
+use crate::ui::{Icon, Menu, MenuAction, MenuItem, View};
+
+use super::App;
+
+pub struct About;
+
+const ICON: Icon = Icon(&[
+    "......####......",
+    "....##....##....",
+    "...#........#...",
+    "..#....##....#..",
+    ".#.....##.....#.",
+    ".#............#.",
+    "#.....###......#",
+    "#......##......#",
+    "#......##......#",
+    "#......##......#",
+    ".#.....##.....#.",
+    ".#....####....#.",
+    "..#..........#..",
+    "...#........#...",
+    "....##....##....",
+    "......####......",
+]);
+
+impl App for About {
+    fn name(&self) -> &str {
+        "About"
+    }
+
+    fn icon(&self) -> &Icon {
+        &ICON
+    }
+
+    fn open(&self) -> Box<dyn View> {
+        Box::new(Menu::new(
+            "About",
+            vec![
+                MenuItem {
+                    label: "Version: 0.1.0".into(),
+                    action: MenuAction::None,
+                },
+                MenuItem {
+                    label: "License: MIT".into(),
+                    action: MenuAction::None,
+                },
+            ],
+        ))
+    }
+}
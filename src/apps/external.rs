@@ -0,0 +1,183 @@
+//! Apps that are separate executables in the `apps` directory of the config
+//! directory, talking to the pager over stdin and stdout
+//!
+//! The protocol is described with `ExternalView`, which hosts them.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::App;
+use crate::config;
+use crate::platform;
+use crate::ui::View;
+use crate::ui::external::ExternalView;
+
+pub const DIR: &str = "apps";
+
+/// How long a closed app gets to exit on its own before it is killed
+const GRACE: Duration = Duration::from_secs(1);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Lines held for the view before more are dropped, it only reads them
+/// while on top of the stack
+const LINE_BACKLOG: usize = 256;
+
+pub struct External {
+    name: String,
+    path: PathBuf,
+}
+
+impl App for External {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn open(&self) -> Box<dyn View> {
+        Box::new(ExternalView::new(&self.name, &self.path))
+    }
+}
+
+/// Executables in the apps directory, by file name
+pub fn discover() -> Vec<External> {
+    let dir = config::path(DIR);
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            eprintln!("Error reading {}: {}", dir.display(), e);
+            return Vec::new();
+        }
+    };
+    let mut apps: Vec<External> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_executable(path))
+        .map(|path| External {
+            name: display_name(&path),
+            path,
+        })
+        .collect();
+    apps.sort_by(|a, b| a.name.cmp(&b.name));
+    apps
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+/// `disk-check.sh` is shown as `Disk check`
+fn display_name(path: &Path) -> String {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut chars = stem
+        .chars()
+        .map(|c| if c == '-' || c == '_' { ' ' } else { c });
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// A running app, lines it prints arrive on `lines` and wake the main loop
+pub struct Process {
+    child: Child,
+    input: Sender<String>,
+    pub lines: Receiver<String>,
+}
+
+impl Process {
+    pub fn spawn(path: &Path) -> io::Result<Self> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            // Its errors go to the pager's log
+            .stderr(Stdio::inherit())
+            .process_group(0)
+            .spawn()?;
+
+        let stdout = child.stdout.take().unwrap();
+        let (line_tx, lines) = mpsc::sync_channel(LINE_BACKLOG);
+        thread::spawn(move || {
+            let mut dropping = false;
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                // Keep reading so the app never stalls on a full pipe
+                match line_tx.try_send(line) {
+                    Ok(()) => dropping = false,
+                    Err(TrySendError::Full(_)) => {
+                        if !dropping {
+                            eprintln!("App output backed up, dropping lines");
+                        }
+                        dropping = true;
+                    }
+                    Err(TrySendError::Disconnected(_)) => break,
+                }
+                platform::wake();
+            }
+            // Dropping the sender tells the view the app is gone
+            drop(line_tx);
+            platform::wake();
+        });
+
+        // Written from a thread so a stuck app never blocks drawing
+        let stdin = child.stdin.take().unwrap();
+        let (input, input_rx) = mpsc::channel();
+        thread::spawn(move || write_lines(stdin, input_rx));
+
+        Ok(Self {
+            child,
+            input,
+            lines,
+        })
+    }
+
+    /// Sends one line, dropped if the app has stopped reading
+    pub fn send(&self, line: String) {
+        let _ = self.input.send(line);
+    }
+
+    /// `exit 0` once the app has exited
+    pub fn exit_status(&mut self) -> Option<String> {
+        let status = self.child.try_wait().ok()??;
+        Some(match status.code() {
+            Some(code) => format!("exit {}", code),
+            None => "killed".to_string(),
+        })
+    }
+
+    /// Closes stdin, which asks the app to quit, and kills it if it is
+    /// still around after a grace period
+    pub fn close(self) {
+        let Self {
+            mut child, input, ..
+        } = self;
+        drop(input);
+        thread::spawn(move || {
+            let deadline = Instant::now() + GRACE;
+            while let Ok(None) = child.try_wait() {
+                if Instant::now() >= deadline {
+                    unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+                    let _ = child.wait();
+                    return;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+    }
+}
+
+fn write_lines(mut stdin: ChildStdin, lines: Receiver<String>) {
+    for line in lines {
+        if writeln!(stdin, "{}", line)
+            .and_then(|_| stdin.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use crate::ui::dialog::Info;
+use crate::ui::{Icon, InboxView, Menu, MenuAction, MenuItem, View};
+
+use super::App;
+
+pub struct Messages;
+
+const ICON: Icon = Icon(&[
+    "................",
+    "................",
+    "................",
+    "################",
+    "##............##",
+    "#.#..........#.#",
+    "#..#........#..#",
+    "#...#......#...#",
+    "#....#....#....#",
+    "#.....#..#.....#",
+    "#......##......#",
+    "#..............#",
+    "#..............#",
+    "################",
+    "................",
+    "................",
+]);
+
+impl App for Messages {
+    fn name(&self) -> &str {
+        "Messages"
+    }
+
+    fn icon(&self) -> &Icon {
+        &ICON
+    }
+
+    fn open(&self) -> Box<dyn View> {
+        Box::new(create_menu())
+    }
+}
+
+fn create_menu() -> Menu {
+    Menu::new(
+        "Messages",
+        vec![
+            MenuItem {
+                label: "Inbox".into(),
+                action: MenuAction::Open(create_inbox),
+            },
+            MenuItem {
+                label: "Sent".into(),
+                action: MenuAction::Open(|| {
+                    Box::new(Info::new("Sent", "Sending messages is not supported yet"))
+                }),
+            },
+            MenuItem {
+                label: "Drafts".into(),
+                action: MenuAction::Open(|| {
+                    Box::new(Info::new("Drafts", "Drafts are not supported yet"))
+                }),
+            },
+        ],
+    )
+}
+
+pub fn create_inbox() -> Box<dyn View> {
+    Box::new(InboxView::new())
+}
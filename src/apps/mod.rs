@@ -0,0 +1,75 @@
+//! Apps shown on the launcher, each opening its own screens and optionally
+//! doing work in the background
+//!
+//! Apps register themselves at startup with `register`, the launcher lists
+//! them in registration order.
+
+mod about;
+pub mod external;
+mod messages;
+mod settings;
+
+pub use messages::create_inbox;
+pub use settings::create_test_alerts_menu;
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::ui::{Icon, View};
+
+pub trait App: Send + Sync {
+    /// Shown under the icon and used to open the app by name
+    fn name(&self) -> &str;
+
+    fn icon(&self) -> &Icon {
+        &Icon::DEFAULT
+    }
+
+    /// Builds the app's first screen each time it is opened
+    fn open(&self) -> Box<dyn View>;
+
+    /// Work started on its own thread at startup, such as polling a server
+    fn background(&self) -> Option<Box<dyn FnOnce() + Send>> {
+        None
+    }
+}
+
+static APPS: Mutex<Vec<Arc<dyn App>>> = Mutex::new(Vec::new());
+
+pub fn register(app: impl App + 'static) {
+    APPS.lock().unwrap().push(Arc::new(app));
+}
+
+/// Messages, Settings and About
+pub fn register_builtin() {
+    register(messages::Messages);
+    register(settings::Settings);
+    register(about::About);
+}
+
+/// Executables in the apps directory, after the built-in apps
+pub fn register_external() {
+    for app in external::discover() {
+        register(app);
+    }
+}
+
+pub fn all() -> Vec<Arc<dyn App>> {
+    APPS.lock().unwrap().clone()
+}
+
+/// App named `name`, ignoring case
+pub fn find(name: &str) -> Option<Arc<dyn App>> {
+    all()
+        .into_iter()
+        .find(|app| app.name().eq_ignore_ascii_case(name))
+}
+
+/// Starts the background work of every registered app
+pub fn start() {
+    for app in all() {
+        if let Some(task) = app.background() {
+            thread::spawn(task);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::inbox;
+use crate::platform::Priority;
+use crate::ui::dialog::Progress;
+use crate::ui::{Icon, Menu, MenuAction, MenuItem, SettingsView, View};
+
+use super::App;
+
+pub struct Settings;
+
+const ICON: Icon = Icon(&[
+    "................",
+    ".......##.......",
+    "...##..##..##...",
+    "...###.##.###...",
+    "....########....",
+    "....###..###....",
+    ".####......####.",
+    ".####......####.",
+    "....##....##....",
+    "....###..###....",
+    "....########....",
+    "...###.##.###...",
+    "...##..##..##...",
+    ".......##.......",
+    "................",
+    "................",
+]);
+
+impl App for Settings {
+    fn name(&self) -> &str {
+        "Settings"
+    }
+
+    fn icon(&self) -> &Icon {
+        &ICON
+    }
+
+    fn open(&self) -> Box<dyn View> {
+        Box::new(create_menu())
+    }
+}
+
+fn create_menu() -> Menu {
+    Menu::new(
+        "Settings",
+        vec![
+            MenuItem {
+                label: "Display".into(),
+                action: MenuAction::Open(|| Box::new(SettingsView::new("Display", "display"))),
+            },
+            MenuItem {
+                label: "Sound".into(),
+                action: MenuAction::Open(|| Box::new(SettingsView::new("Sound", "sound"))),
+            },
+            MenuItem {
+                label: "Do Not Disturb".into(),
+                action: MenuAction::Open(|| Box::new(SettingsView::new("Do Not Disturb", "dnd"))),
+            },
+            MenuItem {
+                label: "Network".into(),
+                action: MenuAction::Open(|| Box::new(SettingsView::new("Network", "network"))),
+            },
+            MenuItem {
+                label: "Security".into(),
+                action: MenuAction::Open(|| Box::new(SettingsView::new("Security", "lock"))),
+            },
+            MenuItem {
+                label: "Test Alerts".into(),
+                action: MenuAction::SubMenu(create_test_alerts_menu),
+            },
+        ],
+    )
+}
+
+pub fn create_test_alerts_menu() -> Menu {
+    Menu::new(
+        "Test Alerts",
+        vec![
+            MenuItem {
+                label: "Test: Low".into(),
+                action: MenuAction::Run(|| inbox::receive("Test alert", Priority::Low)),
+            },
+            MenuItem {
+                label: "Test: Normal".into(),
+                action: MenuAction::Run(|| inbox::receive("Test alert", Priority::Normal)),
+            },
+            MenuItem {
+                label: "Test: High".into(),
+                action: MenuAction::Run(|| inbox::receive("Test alert", Priority::High)),
+            },
+            MenuItem {
+                label: "Test: Critical".into(),
+                action: MenuAction::Run(|| inbox::receive("Test alert", Priority::Critical)),
+            },
+            MenuItem {
+                label: "Test: Progress".into(),
+                action: MenuAction::Open(create_test_progress),
+            },
+        ],
+    )
+}
+
+/// Five seconds of fake work to try the progress dialog
+fn create_test_progress() -> Box<dyn View> {
+    Box::new(Progress::spawn(
+        "Test: Progress",
+        "Working...",
+        |progress| {
+            for step in 1..=50 {
+                if progress.is_cancelled() {
+                    return;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                progress.set(step as f32 / 50.0);
+            }
+        },
+    ))
+}
@@ -0,0 +1,63 @@
+//! Configuration files: location, `key = value` parsing and atomic writes
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory holding the configuration files, `OPENPAGER_CONFIG_DIR` wins
+pub fn dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("OPENPAGER_CONFIG_DIR") {
+        return dir.into();
+    }
+    if cfg!(target_arch = "mips") {
+        return "/etc/openpager".into();
+    }
+    match (
+        std::env::var_os("XDG_CONFIG_HOME"),
+        std::env::var_os("HOME"),
+    ) {
+        (Some(xdg), _) => Path::new(&xdg).join("openpager"),
+        (None, Some(home)) => Path::new(&home).join(".config/openpager"),
+        (None, None) => ".".into(),
+    }
+}
+
+pub fn path(name: &str) -> PathBuf {
+    dir().join(name)
+}
+
+/// Parses `key = value` lines, keys under a `[section]` become `section.key`
+pub fn parse(text: &str) -> Vec<(String, String)> {
+    let mut section = String::new();
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim();
+            let key = if section.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", section, key)
+            };
+            entries.push((key, value.trim().to_string()));
+        }
+    }
+    entries
+}
+
+/// Writes through a temporary file and a rename, never leaving half a file
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
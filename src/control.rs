@@ -0,0 +1,201 @@
+//! Control socket for scripts and debugging, one command per line and one
+//! `ok ...` or `error ...` line back, e.g. `echo screenshot | nc -U <socket>`
+//!
+//! - `screenshot` saves a PNG and replies with its path
+//! - `views` lists the view stack from the root, `title [selected]` joined
+//!   by ` / `
+//! - `press <action>...` feeds in actions such as `down down select`, not
+//!   while the lock screen is up
+//! - `open <path>` goes back to the main menu and opens the items along a
+//!   path such as `Settings/Display`
+//! - `dump` replies with the screen as a base64 PNG
+//!
+//! Only the user running the pager may connect, the socket is mode 0600 in
+//! a directory nobody else can enter.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::platform::{self, Action};
+
+pub enum Command {
+    Screenshot,
+    Views,
+    Press(Vec<Action>),
+    Open(Vec<String>),
+    Dump,
+}
+
+impl Command {
+    fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (name, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+        match name {
+            "screenshot" => Ok(Command::Screenshot),
+            "views" => Ok(Command::Views),
+            "press" => {
+                let actions = args
+                    .split_whitespace()
+                    .map(|word| {
+                        Action::from_name(word).ok_or_else(|| format!("unknown action {:?}", word))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if actions.is_empty() {
+                    return Err("press needs at least one action".into());
+                }
+                Ok(Command::Press(actions))
+            }
+            "open" => Ok(Command::Open(
+                args.split('/')
+                    .map(str::trim)
+                    .filter(|segment| !segment.is_empty())
+                    .map(String::from)
+                    .collect(),
+            )),
+            "dump" => Ok(Command::Dump),
+            _ => Err(format!("unknown command {:?}", name)),
+        }
+    }
+}
+
+/// A command waiting for the main loop, which answers with `reply`
+pub struct Request {
+    pub command: Command,
+    stream: UnixStream,
+}
+
+impl Request {
+    pub fn reply(mut self, result: Result<String, String>) {
+        let line = match result {
+            Ok(text) => format!("ok {}\n", text),
+            Err(text) => format!("error {}\n", text),
+        };
+        let _ = self.stream.write_all(line.as_bytes());
+    }
+}
+
+/// `OPENPAGER_SOCKET`, else `openpager.sock` in the runtime directory or,
+/// without one, in a private directory under the temp directory
+fn socket_path() -> io::Result<PathBuf> {
+    if let Some(path) = std::env::var_os("OPENPAGER_SOCKET") {
+        return Ok(path.into());
+    }
+    if cfg!(target_arch = "mips") {
+        return Ok("/var/run/openpager.sock".into());
+    }
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => dir.into(),
+        None => private_dir()?,
+    };
+    Ok(dir.join("openpager.sock"))
+}
+
+/// `openpager-<uid>` in the temp directory, refused unless it is ours and
+/// closed to everyone else
+fn private_dir() -> io::Result<PathBuf> {
+    let uid = unsafe { libc::getuid() };
+    let dir = std::env::temp_dir().join(format!("openpager-{}", uid));
+    if let Err(e) = fs::DirBuilder::new().mode(0o700).create(&dir)
+        && e.kind() != io::ErrorKind::AlreadyExists
+    {
+        return Err(e);
+    }
+    let meta = fs::symlink_metadata(&dir)?;
+    if !meta.is_dir() || meta.uid() != uid || meta.mode() & 0o077 != 0 {
+        return Err(io::Error::other(format!(
+            "{} is not a private directory",
+            dir.display()
+        )));
+    }
+    Ok(dir)
+}
+
+/// Listens on the control socket, requests arrive on the returned channel
+/// and wake the main loop
+pub fn start() -> Receiver<Request> {
+    let (tx, rx) = mpsc::channel();
+    let path = match socket_path() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Error opening control socket: {}", e);
+            return rx;
+        }
+    };
+    // A previous run may have left its socket behind, anything else there
+    // is not ours to delete
+    if fs::symlink_metadata(&path).is_ok_and(|meta| meta.file_type().is_socket()) {
+        let _ = fs::remove_file(&path);
+    }
+    match UnixListener::bind(&path) {
+        Ok(listener) => {
+            if let Err(e) = fs::set_permissions(&path, fs::Permissions::from_mode(0o600)) {
+                eprintln!("Error securing control socket {}: {}", path.display(), e);
+                return rx;
+            }
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let tx = tx.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = serve(stream, tx) {
+                            eprintln!("Control connection error: {}", e);
+                        }
+                    });
+                }
+            });
+        }
+        Err(e) => eprintln!("Error opening control socket {}: {}", path.display(), e),
+    }
+    rx
+}
+
+fn serve(stream: UnixStream, tx: Sender<Request>) -> io::Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match Command::parse(&line) {
+            Ok(command) => {
+                let request = Request {
+                    command,
+                    stream: stream.try_clone()?,
+                };
+                if tx.send(request).is_err() {
+                    break;
+                }
+                platform::wake();
+            }
+            Err(e) => (&stream).write_all(format!("error {}\n", e).as_bytes())?,
+        }
+    }
+    Ok(())
+}
+
+/// Standard base64 with padding, for binary replies
+pub fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
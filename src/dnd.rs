@@ -0,0 +1,42 @@
+//! Do-not-disturb, with optional quiet hours in local time
+
+use crate::platform::clock;
+use crate::settings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Off,
+    On,
+    Scheduled,
+}
+
+/// Follows the order of the `dnd.mode` choices
+pub fn mode() -> Mode {
+    match settings::get_choice("dnd.mode") {
+        1 => Mode::On,
+        2 => Mode::Scheduled,
+        _ => Mode::Off,
+    }
+}
+
+/// Whether non-critical alerts should be silenced right now
+pub fn active() -> bool {
+    match mode() {
+        Mode::Off => false,
+        Mode::On => true,
+        Mode::Scheduled => {
+            let start = settings::get_int("dnd.start") as u16 * 60;
+            let end = settings::get_int("dnd.end") as u16 * 60;
+            in_window(clock::local_time().minutes(), start, end)
+        }
+    }
+}
+
+/// Quiet hours may wrap past midnight
+fn in_window(now: u16, start: u16, end: u16) -> bool {
+    if start <= end {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    }
+}
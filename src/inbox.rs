@@ -0,0 +1,473 @@
+//! Message store shared between the UI and message sources
+//!
+//! Messages with the same fingerprint collapse into one that counts them,
+//! so a flapping alert takes a single line, and a matching resolved
+//! message marks it resolved.
+
+use std::sync::Mutex;
+
+use crate::dnd;
+use crate::json::{self, Value};
+use crate::platform::clock::{self, LocalTime};
+use crate::platform::{self, Priority, leds};
+
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub id: u32,
+    pub subject: String,
+    pub priority: Priority,
+    pub read: bool,
+    /// Repeats of an alert share a fingerprint and one message
+    pub fingerprint: String,
+    /// Times the alert arrived
+    pub count: u32,
+    pub last_seen: LocalTime,
+    /// When a matching resolved message arrived, if one has since
+    pub resolved: Option<LocalTime>,
+    /// When the source says the alert started firing
+    pub starts_at: Option<String>,
+}
+
+impl Message {
+    /// Unread and not resolved yet
+    pub fn is_pending(&self) -> bool {
+        !self.read && self.resolved.is_none()
+    }
+}
+
+/// A message as a source hands it in
+#[derive(Debug, Clone)]
+pub struct Incoming {
+    pub subject: String,
+    /// Normal when the source has no opinion
+    pub priority: Option<Priority>,
+    /// Groups repeats, the subject when the source has none
+    pub fingerprint: Option<String>,
+    /// Resolves the alert with the same fingerprint
+    pub resolved: bool,
+    /// Start of the firing, a resend of the same firing changes nothing
+    pub starts_at: Option<String>,
+}
+
+/// Subjects starting with these resolve the alert named by the rest
+const RESOLVED_PREFIXES: &[&str] = &["resolved:", "[resolved]"];
+
+impl Incoming {
+    /// Message with `subject`, a subject such as `Resolved: CPU 95%`
+    /// resolves the `CPU 95%` alert
+    pub fn new(subject: &str, priority: Option<Priority>) -> Self {
+        let subject = subject.trim();
+        let rest = RESOLVED_PREFIXES.iter().find_map(|prefix| {
+            subject
+                .get(..prefix.len())
+                .filter(|head| head.eq_ignore_ascii_case(prefix))
+                .map(|_| subject[prefix.len()..].trim_start())
+        });
+        Self {
+            subject: rest.unwrap_or(subject).to_string(),
+            priority,
+            fingerprint: None,
+            resolved: rest.is_some(),
+            starts_at: None,
+        }
+    }
+
+    fn fingerprint(&self) -> String {
+        match &self.fingerprint {
+            Some(fingerprint) => fingerprint.clone(),
+            // Case and spacing are not worth a second message
+            None => self
+                .subject
+                .split_whitespace()
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+/// Messages kept, past this the oldest read or resolved ones go first
+const MAX_MESSAGES: usize = 200;
+
+struct Inbox {
+    next_id: u32,
+    messages: Vec<Message>,
+}
+
+static INBOX: Mutex<Inbox> = Mutex::new(Inbox {
+    next_id: 1,
+    messages: Vec::new(),
+});
+
+/// Stores a new message and alerts the user, only critical messages
+/// break through do-not-disturb
+pub fn receive(subject: &str, priority: Priority) {
+    deliver(Incoming::new(subject, Some(priority)));
+}
+
+/// Files a message from a source and alerts the user unless it only
+/// repeats one still pending or resolves one
+pub fn deliver(incoming: Incoming) {
+    if let Some(priority) = file(incoming)
+        && (priority == Priority::Critical || !dnd::active())
+    {
+        platform::notify(priority);
+    }
+}
+
+/// Stores a new message without sound or vibration
+pub fn store(subject: &str, priority: Priority) {
+    file(Incoming::new(subject, Some(priority)));
+}
+
+/// Adds the message, or updates the one with its fingerprint, returns the
+/// priority to alert with if the user should hear about it
+fn file(incoming: Incoming) -> Option<Priority> {
+    let fingerprint = incoming.fingerprint();
+    let priority = incoming.priority.unwrap_or(Priority::Normal);
+    let now = clock::local_time();
+    let mut inbox = INBOX.lock().unwrap();
+    let existing = inbox
+        .messages
+        .iter()
+        .position(|m| m.fingerprint == fingerprint);
+
+    let alert = match (existing, incoming.resolved) {
+        (Some(i), true) => {
+            let message = &mut inbox.messages[i];
+            message.resolved = message.resolved.or(Some(now));
+            None
+        }
+        // Alertmanager and Grafana send firing alerts again every few
+        // hours, that is not news
+        (Some(i), false)
+            if incoming.starts_at.is_some()
+                && inbox.messages[i].starts_at == incoming.starts_at =>
+        {
+            None
+        }
+        (Some(i), false) => {
+            // Repeats move to the top, they only alert again once the
+            // user has seen the last one or it got worse
+            let mut message = inbox.messages.remove(i);
+            let quiet = message.is_pending() && priority <= message.priority;
+            message.subject = incoming.subject;
+            message.priority = priority;
+            message.read = false;
+            message.count += 1;
+            message.last_seen = now;
+            message.resolved = None;
+            message.starts_at = incoming.starts_at;
+            inbox.messages.insert(0, message);
+            (!quiet).then_some(priority)
+        }
+        (None, resolved) => {
+            // A resolution for an alert that is not here is kept for the
+            // record, already resolved
+            let id = inbox.next_id;
+            inbox.next_id += 1;
+            inbox.messages.insert(
+                0,
+                Message {
+                    id,
+                    subject: incoming.subject,
+                    priority,
+                    read: false,
+                    fingerprint,
+                    count: 1,
+                    last_seen: now,
+                    resolved: resolved.then_some(now),
+                    starts_at: incoming.starts_at,
+                },
+            );
+            (!resolved).then_some(priority)
+        }
+    };
+    trim(&mut inbox.messages);
+    update_leds(&inbox);
+    platform::wake();
+    alert
+}
+
+/// Drops messages past `MAX_MESSAGES`, the oldest one no longer pending
+/// each time, or the oldest of all when everything is pending
+fn trim(messages: &mut Vec<Message>) {
+    while messages.len() > MAX_MESSAGES {
+        let i = messages
+            .iter()
+            .rposition(|m| !m.is_pending())
+            .unwrap_or(messages.len() - 1);
+        messages.remove(i);
+    }
+}
+
+/// Message in a payload from a source, either plain text or a JSON object
+/// with `subject` (or `message` or `text`) and optionally `priority`,
+/// `fingerprint` and `status`, where `resolved` resolves the alert. `None`
+/// when there is no subject
+pub fn parse_payload(text: &str) -> Option<Incoming> {
+    let text = text.trim();
+    let incoming = match json::parse(text) {
+        Ok(value @ Value::Object(_)) => {
+            let field = |key: &str| value.get(key).and_then(Value::as_str);
+            let subject = ["subject", "message", "text"]
+                .iter()
+                .find_map(|key| field(key))?;
+            let priority = field("priority").and_then(Priority::from_name);
+            let mut incoming = Incoming::new(subject, priority);
+            incoming.fingerprint = field("fingerprint").map(String::from);
+            incoming.resolved |= field("status") == Some("resolved");
+            incoming
+        }
+        _ => Incoming::new(text, None),
+    };
+    if incoming.subject.is_empty() {
+        return None;
+    }
+    Some(incoming)
+}
+
+/// Snapshot of all messages, newest first
+pub fn messages() -> Vec<Message> {
+    INBOX.lock().unwrap().messages.clone()
+}
+
+/// Whether the inbox has a message with the fingerprint of `incoming`
+pub fn contains(incoming: &Incoming) -> bool {
+    let fingerprint = incoming.fingerprint();
+    INBOX
+        .lock()
+        .unwrap()
+        .messages
+        .iter()
+        .any(|m| m.fingerprint == fingerprint)
+}
+
+/// Number of unread messages still firing
+pub fn pending() -> usize {
+    INBOX
+        .lock()
+        .unwrap()
+        .messages
+        .iter()
+        .filter(|m| m.is_pending())
+        .count()
+}
+
+/// Marks a message read, the LEDs go dark once nothing is pending
+pub fn acknowledge(id: u32) {
+    let mut inbox = INBOX.lock().unwrap();
+    if let Some(message) = inbox.messages.iter_mut().find(|m| m.id == id) {
+        message.read = true;
+    }
+    update_leds(&inbox);
+}
+
+fn update_leds(inbox: &Inbox) {
+    let pending = inbox
+        .messages
+        .iter()
+        .filter(|m| m.is_pending())
+        .map(|m| m.priority)
+        .max();
+    leds::show(pending);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The inbox is shared with every other test, so each test uses its own
+    // fingerprints and never assumes what else is in there
+
+    fn incoming(subject: &str, fingerprint: &str, priority: Priority) -> Incoming {
+        let mut incoming = Incoming::new(subject, Some(priority));
+        incoming.fingerprint = Some(fingerprint.into());
+        incoming
+    }
+
+    fn find(fingerprint: &str) -> Message {
+        messages()
+            .into_iter()
+            .find(|m| m.fingerprint == fingerprint)
+            .expect(fingerprint)
+    }
+
+    fn position(fingerprint: &str) -> usize {
+        messages()
+            .iter()
+            .position(|m| m.fingerprint == fingerprint)
+            .expect(fingerprint)
+    }
+
+    #[test]
+    fn resolved_prefix() {
+        let incoming = Incoming::new("  Resolved:  CPU 95%", None);
+        assert_eq!(incoming.subject, "CPU 95%");
+        assert!(incoming.resolved);
+        let incoming = Incoming::new("[RESOLVED] CPU 95%", None);
+        assert_eq!(incoming.subject, "CPU 95%");
+        assert!(incoming.resolved);
+        let incoming = Incoming::new("Not resolved: CPU 95%", None);
+        assert_eq!(incoming.subject, "Not resolved: CPU 95%");
+        assert!(!incoming.resolved);
+        // Without a fingerprint the subject names the alert
+        assert_eq!(Incoming::new("CPU  95%", None).fingerprint(), "cpu 95%");
+    }
+
+    #[test]
+    fn payloads() {
+        let plain = parse_payload("  Disk full \n").unwrap();
+        assert_eq!(plain.subject, "Disk full");
+        assert_eq!(plain.priority, None);
+
+        let json = parse_payload(
+            r#"{"message": "Disk full", "priority": "high", "fingerprint": "disk-1"}"#,
+        )
+        .unwrap();
+        assert_eq!(json.subject, "Disk full");
+        assert_eq!(json.priority, Some(Priority::High));
+        assert_eq!(json.fingerprint.as_deref(), Some("disk-1"));
+        assert!(!json.resolved);
+
+        let resolved = parse_payload(r#"{"text": "Disk full", "status": "resolved"}"#).unwrap();
+        assert!(resolved.resolved);
+        let prefixed = parse_payload(r#"{"subject": "Resolved: Disk full"}"#).unwrap();
+        assert_eq!(prefixed.subject, "Disk full");
+        assert!(prefixed.resolved);
+
+        // Other JSON is plain text
+        assert_eq!(parse_payload("42").unwrap().subject, "42");
+        assert!(parse_payload("   ").is_none());
+        assert!(parse_payload(r#"{"priority": "high"}"#).is_none());
+        assert!(parse_payload(r#"{"subject": "Resolved:"}"#).is_none());
+    }
+
+    #[test]
+    fn repeats_collapse() {
+        let first = file(incoming("Repeat A", "test-repeat-a", Priority::Normal));
+        assert_eq!(first, Some(Priority::Normal));
+        file(incoming("Repeat B", "test-repeat-b", Priority::Normal));
+        assert!(position("test-repeat-b") < position("test-repeat-a"));
+
+        // Still pending and no worse, so it only counts
+        let before = clock::local_time();
+        let repeat = file(incoming("Repeat A again", "test-repeat-a", Priority::Low));
+        let after = clock::local_time();
+        assert_eq!(repeat, None);
+        let message = find("test-repeat-a");
+        assert_eq!(message.count, 2);
+        assert_eq!(message.subject, "Repeat A again");
+        assert_eq!(message.priority, Priority::Low);
+        assert!([before, after].contains(&message.last_seen));
+        assert!(position("test-repeat-a") < position("test-repeat-b"));
+
+        // Getting worse alerts again
+        let worse = file(incoming("Repeat A", "test-repeat-a", Priority::High));
+        assert_eq!(worse, Some(Priority::High));
+
+        // So does a repeat of one already read, which comes back unread
+        acknowledge(message.id);
+        assert!(!find("test-repeat-a").is_pending());
+        let after_read = file(incoming("Repeat A", "test-repeat-a", Priority::High));
+        assert_eq!(after_read, Some(Priority::High));
+        let message = find("test-repeat-a");
+        assert_eq!(message.count, 4);
+        assert!(message.is_pending());
+    }
+
+    #[test]
+    fn resends_change_nothing() {
+        let firing = |starts_at: &str| {
+            let mut incoming = incoming("Resend", "test-resend", Priority::High);
+            incoming.starts_at = Some(starts_at.into());
+            incoming
+        };
+        assert_eq!(file(firing("10:00")), Some(Priority::High));
+        file(incoming("Resend B", "test-resend-b", Priority::Normal));
+        assert_eq!(file(firing("10:00")), None);
+        assert_eq!(find("test-resend").count, 1);
+        assert!(position("test-resend-b") < position("test-resend"));
+
+        // A new firing of the same alert is a repeat
+        acknowledge(find("test-resend").id);
+        assert_eq!(file(firing("11:00")), Some(Priority::High));
+        let message = find("test-resend");
+        assert_eq!(message.count, 2);
+        assert_eq!(message.starts_at.as_deref(), Some("11:00"));
+        assert!(position("test-resend") < position("test-resend-b"));
+    }
+
+    #[test]
+    fn subjects_collapse_without_fingerprint() {
+        file(Incoming::new("Test dedup  SUBJECT", Some(Priority::Normal)));
+        file(Incoming::new("test dedup subject", Some(Priority::Normal)));
+        let message = find("test dedup subject");
+        assert_eq!(message.count, 2);
+        assert_eq!(message.subject, "test dedup subject");
+    }
+
+    #[test]
+    fn resolutions() {
+        file(incoming("Resolve me", "test-resolve", Priority::High));
+        let resolved = file(Incoming::new("Resolved: Resolve me", None));
+        // The subject fingerprint does not match this one
+        assert_eq!(resolved, None);
+        assert!(find("resolve me").resolved.is_some());
+        assert!(find("test-resolve").is_pending());
+
+        let mut resolution = incoming("Resolve me", "test-resolve", Priority::High);
+        resolution.resolved = true;
+        assert_eq!(file(resolution.clone()), None);
+        let message = find("test-resolve");
+        let at = message.resolved.expect("resolved");
+        assert!(!message.is_pending());
+        assert_eq!(message.count, 1);
+
+        // Resolving twice keeps the first time, firing again starts over
+        file(resolution);
+        assert_eq!(find("test-resolve").resolved, Some(at));
+        let again = file(incoming("Resolve me", "test-resolve", Priority::High));
+        assert_eq!(again, Some(Priority::High));
+        let message = find("test-resolve");
+        assert_eq!(message.resolved, None);
+        assert_eq!(message.count, 2);
+    }
+
+    fn message(id: u32, read: bool) -> Message {
+        Message {
+            id,
+            subject: format!("Message {}", id),
+            priority: Priority::Normal,
+            read,
+            fingerprint: format!("message {}", id),
+            count: 1,
+            last_seen: LocalTime { hour: 0, minute: 0 },
+            resolved: None,
+            starts_at: None,
+        }
+    }
+
+    #[test]
+    fn inbox_cap() {
+        // Newest first, every third message read
+        let mut messages: Vec<Message> = (0..MAX_MESSAGES as u32 + 3)
+            .rev()
+            .map(|id| message(id, id % 3 == 0))
+            .collect();
+        trim(&mut messages);
+        assert_eq!(messages.len(), MAX_MESSAGES);
+        let gone: Vec<u32> = (0..MAX_MESSAGES as u32 + 3)
+            .filter(|id| !messages.iter().any(|m| m.id == *id))
+            .collect();
+        assert_eq!(gone, [0, 3, 6]);
+
+        let mut unread: Vec<Message> = (0..MAX_MESSAGES as u32 + 2)
+            .rev()
+            .map(|id| message(id, false))
+            .collect();
+        trim(&mut unread);
+        assert_eq!(unread.len(), MAX_MESSAGES);
+        assert_eq!(unread.last().unwrap().id, 2);
+    }
+}
@@ -0,0 +1,356 @@
+//! Small JSON reader for the menu file and other configuration that does
+//! not fit `key = value` lines
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Members in file order
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Member of an object, `None` for missing keys and other values
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+/// Parse error with the line it was found on
+#[derive(Debug)]
+pub struct Error {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+pub fn parse(text: &str) -> Result<Value, Error> {
+    let mut parser = Parser {
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.pos < parser.bytes.len() {
+        return Err(parser.error("trailing characters after the value"));
+    }
+    Ok(value)
+}
+
+/// Deeper nesting than any sane file, keeps recursion off the stack limit
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>) -> Error {
+        let line = self.bytes[..self.pos.min(self.bytes.len())]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count();
+        Error {
+            line: line + 1,
+            message: message.into(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\r' | b'\n') = self.bytes.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), Error> {
+        if self.peek() != Some(byte) {
+            return Err(self.error(format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, Error> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+        match self.peek() {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => {
+                for (word, value) in [
+                    ("true", Value::Bool(true)),
+                    ("false", Value::Bool(false)),
+                    ("null", Value::Null),
+                ] {
+                    if self.bytes[self.pos..].starts_with(word.as_bytes()) {
+                        self.pos += word.len();
+                        return Ok(value);
+                    }
+                }
+                Err(self.error("expected a value"))
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value, Error> {
+        self.expect(b'{')?;
+        let mut members = Vec::new();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(members));
+        }
+        loop {
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a member name"));
+            }
+            let key = self.string()?;
+            self.expect(b':')?;
+            members.push((key, self.value(depth + 1)?));
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(members));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value, Error> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, Error> {
+        let start = self.pos;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.bytes.get(self.pos) {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap();
+        text.parse()
+            .map(Value::Number)
+            .map_err(|_| self.error(format!("bad number {:?}", text)))
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        self.expect(b'"')?;
+        let mut out = Vec::new();
+        loop {
+            let Some(&b) = self.bytes.get(self.pos) else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += 1;
+            match b {
+                b'"' => break,
+                b'\\' => {
+                    let Some(&escape) = self.bytes.get(self.pos) else {
+                        return Err(self.error("unterminated string"));
+                    };
+                    self.pos += 1;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("bad escape in string")),
+                    };
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                b if b < 0x20 => return Err(self.error("control character in string")),
+                b => out.push(b),
+            }
+        }
+        // The input is a str and escapes are pushed as UTF-8
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    /// The four hex digits after `\u`, joining surrogate pairs
+    fn unicode_escape(&mut self) -> Result<char, Error> {
+        let high = self.hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| self.error("bad \\u escape"));
+        }
+        if !self.bytes[self.pos..].starts_with(b"\\u") {
+            return Err(self.error("unpaired surrogate"));
+        }
+        self.pos += 2;
+        let low = self.hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.error("unpaired surrogate"));
+        }
+        let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+        char::from_u32(code).ok_or_else(|| self.error("bad \\u escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, Error> {
+        let digits = self
+            .bytes
+            .get(self.pos..self.pos + 4)
+            // `from_str_radix` would take a sign too
+            .filter(|d| d.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| self.error("bad \\u escape"))?;
+        self.pos += 4;
+        Ok(digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> Error {
+        parse(text).expect_err(text)
+    }
+
+    #[test]
+    fn values() {
+        let value = parse(r#" {"a": [1, -2.5e3, true, false, null], "b": {}} "#).unwrap();
+        assert_eq!(
+            value.get("a").and_then(Value::as_array).unwrap(),
+            [
+                Value::Number(1.0),
+                Value::Number(-2500.0),
+                Value::Bool(true),
+                Value::Bool(false),
+                Value::Null,
+            ]
+        );
+        assert_eq!(value.get("b"), Some(&Value::Object(Vec::new())));
+        assert_eq!(value.get("c"), None);
+    }
+
+    #[test]
+    fn escapes() {
+        let value = parse(r#""\"\\\/\b\f\n\r\t\u0041\u00e9""#).unwrap();
+        assert_eq!(value.as_str(), Some("\"\\/\u{8}\u{c}\n\r\tA\u{e9}"));
+        assert_eq!(error(r#""\x""#).message, "bad escape in string");
+        assert_eq!(error(r#""\u12""#).message, "bad \\u escape");
+        assert_eq!(error(r#""\u+041""#).message, "bad \\u escape");
+        assert_eq!(error("\"a\nb\"").message, "control character in string");
+        assert_eq!(error(r#""abc"#).message, "unterminated string");
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        let value = parse(r#""\ud83d\ude00""#).unwrap();
+        assert_eq!(value.as_str(), Some("\u{1F600}"));
+        assert_eq!(error(r#""\ud83d""#).message, "unpaired surrogate");
+        assert_eq!(error(r#""\ud83dx""#).message, "unpaired surrogate");
+        assert_eq!(error(r#""\ud83d\u0041""#).message, "unpaired surrogate");
+        // A low surrogate on its own is not a character either
+        assert_eq!(error(r#""\ude00""#).message, "bad \\u escape");
+    }
+
+    #[test]
+    fn depth_limit() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH + 1)).is_ok());
+        assert_eq!(error(&nested(MAX_DEPTH + 2)).message, "nested too deeply");
+    }
+
+    #[test]
+    fn trailing_characters() {
+        assert_eq!(error("{} x").message, "trailing characters after the value");
+        assert_eq!(
+            error("truex").message,
+            "trailing characters after the value"
+        );
+        assert_eq!(
+            error("[1] [2]").message,
+            "trailing characters after the value"
+        );
+        assert!(parse("  [1]  \n").is_ok());
+    }
+
+    #[test]
+    fn bad_numbers() {
+        for text in ["-", "1.2.3", "1e", "--1", "1-2", "2e+"] {
+            assert_eq!(error(text).message, format!("bad number {:?}", text));
+        }
+    }
+
+    #[test]
+    fn structure_errors() {
+        assert_eq!(error("").message, "unexpected end of input");
+        assert_eq!(error("[1,]").message, "expected a value");
+        assert_eq!(error("[1 2]").message, "expected ',' or ']'");
+        assert_eq!(error(r#"{"a" 1}"#).message, "expected ':'");
+        assert_eq!(error(r#"{"a": 1,}"#).message, "expected a member name");
+        assert_eq!(error(r#"{"a": 1 "b": 2}"#).message, "expected ',' or '}'");
+    }
+
+    #[test]
+    fn error_lines() {
+        let err = error("{\n  \"a\": 1,\n  \"b\": x\n}");
+        assert_eq!(err.line, 3);
+        assert_eq!(err.to_string(), "line 3: expected a value");
+        assert_eq!(error("[\n\n\n").line, 4);
+    }
+}
@@ -1,11 +1,33 @@
 pub const DISPLAY_WIDTH: u32 = 480;
 pub const DISPLAY_HEIGHT: u32 = 222;
 
+mod apps;
+mod config;
+mod control;
+mod dnd;
+mod inbox;
+mod json;
+mod menus;
+mod mqtt;
 mod platform;
+mod recording;
+mod rfb;
+mod screenshot;
+mod settings;
+mod shell;
 mod ui;
+mod webhook;
 
-use platform::{Platform, RenderBuffer};
-use ui::{Menu, MenuAction, MenuItem, StatusBar, View, ViewResult};
+use std::rc::Rc;
+use std::time::Instant;
+
+use control::Command;
+use platform::{Action, Platform, Priority, RenderBuffer};
+use recording::{Recorder, Replay};
+use ui::clockface::ClockFace;
+use ui::launcher::Launcher;
+use ui::lock::LockView;
+use ui::{MenuAction, StatusBar, View, ViewResult, ViewStack};
 
 fn main() {
     if let Err(e) = run() {
@@ -13,167 +35,258 @@ fn main() {
     }
 }
 
-fn create_main_menu() -> Menu {
-    Menu::new(
-        "Main Menu",
-        vec![
-            MenuItem {
-                label: "Messages",
-                action: MenuAction::SubMenu(create_messages_menu),
-            },
-            MenuItem {
-                label: "Settings",
-                action: MenuAction::SubMenu(create_settings_menu),
-            },
-            MenuItem {
-                label: "About",
-                action: MenuAction::SubMenu(create_about_menu),
-            },
-            MenuItem {
-                label: "Exit",
-                action: MenuAction::Exit,
-            },
-        ],
-    )
+/// Screens the menu file can open by name, apps go by their names
+fn screen(name: &str) -> Option<MenuAction> {
+    match name {
+        "apps" => Some(MenuAction::Open(|| Box::new(Launcher::new()))),
+        "inbox" => Some(MenuAction::Open(apps::create_inbox)),
+        "test-alerts" => Some(MenuAction::SubMenu(apps::create_test_alerts_menu)),
+        "exit" => Some(MenuAction::Exit),
+        name => {
+            let app = apps::find(name)?;
+            Some(MenuAction::Build(Rc::new(move || app.open())))
+        }
+    }
+}
+
+/// Demo messages until a real message source is connected
+fn seed_inbox() {
+    let messages = [
+        ("Server alert: CPU 95%", Priority::High),
+        ("Backup completed", Priority::Low),
+        ("New user registered", Priority::Low),
+        ("Payment received", Priority::Normal),
+        ("Disk space warning", Priority::Normal),
+        ("SSL cert expiring", Priority::Normal),
+        ("Deploy successful", Priority::Low),
+        ("Error: DB timeout", Priority::Critical),
+        ("Weekly report ready", Priority::Low),
+        ("Security scan done", Priority::Low),
+        ("New comment on #42", Priority::Low),
+        ("Build failed: main", Priority::High),
+    ];
+    for (subject, priority) in messages.into_iter().rev() {
+        inbox::store(subject, priority);
+    }
 }
 
-fn create_messages_menu() -> Menu {
-    Menu::new(
-        "Messages",
-        vec![
-            MenuItem {
-                label: "Inbox",
-                action: MenuAction::SubMenu(create_inbox),
-            },
-            MenuItem {
-                label: "Sent",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "Drafts",
-                action: MenuAction::None,
-            },
-        ],
-    )
+/// Saves the last drawn frame, replies with the file's path
+fn save_screenshot(render: &RenderBuffer) -> Result<String, String> {
+    match screenshot::save(render) {
+        Ok(path) => {
+            ui::toast::show("Screenshot saved");
+            Ok(path.display().to_string())
+        }
+        Err(e) => {
+            eprintln!("Error saving screenshot: {}", e);
+            ui::toast::show("Could not save screenshot");
+            Err(e.to_string())
+        }
+    }
 }
 
-fn create_inbox() -> Menu {
-    Menu::new(
-        "Inbox",
-        vec![
-            MenuItem {
-                label: "Server alert: CPU 95%",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "Backup completed",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "New user registered",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "Payment received",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "Disk space warning",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "SSL cert expiring",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "Deploy successful",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "Error: DB timeout",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "Weekly report ready",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "Security scan done",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "New comment on #42",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "Build failed: main",
-                action: MenuAction::None,
-            },
-        ],
-    )
+/// Runs an action the way a key press would
+fn dispatch(views: &mut ViewStack, render: &RenderBuffer, action: Action) {
+    let result = match action {
+        Action::Power if !ui::lock::is_locked() => ViewResult::Push(Box::new(LockView::new())),
+        Action::Screenshot => {
+            let _ = save_screenshot(render);
+            ViewResult::None
+        }
+        _ => match views.top_mut() {
+            Some(view) => view.handle(action),
+            None => return,
+        },
+    };
+    views.apply(result);
 }
 
-fn create_settings_menu() -> Menu {
-    Menu::new(
-        "Settings",
-        vec![
-            MenuItem {
-                label: "Display",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "Sound",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "Network",
-                action: MenuAction::None,
-            },
-        ],
-    )
+/// Status bar, views, then toasts on top
+fn draw(render: &mut RenderBuffer, views: &ViewStack, now: Instant) {
+    let theme = ui::theme::active();
+    StatusBar.render(render, &theme);
+    views.render(render, &theme);
+    ui::toast::render(render, &theme, now);
 }
 
-fn create_about_menu() -> Menu {
-    Menu::new(
-        "About",
-        vec![
-            MenuItem {
-                label: "Version: 0.1.0",
-                action: MenuAction::None,
-            },
-            MenuItem {
-                label: "License: MIT",
-                action: MenuAction::None,
-            },
-        ],
-    )
+/// `title [selected]` for each view from the root, joined by ` / `
+fn describe(views: &ViewStack) -> String {
+    let names: Vec<String> = views
+        .iter()
+        .map(|view| match view.selected() {
+            Some(selected) => format!("{} [{}]", view.title(), selected),
+            None => view.title().to_string(),
+        })
+        .collect();
+    names.join(" / ")
+}
+
+fn handle_control(
+    command: &Command,
+    views: &mut ViewStack,
+    render: &mut RenderBuffer,
+) -> Result<String, String> {
+    match command {
+        Command::Screenshot => save_screenshot(render),
+        Command::Views => Ok(describe(views)),
+        Command::Press(actions) => {
+            // Keys from a script could try lock codes without end
+            if ui::lock::is_locked() {
+                return Err("locked".into());
+            }
+            for &action in actions {
+                dispatch(views, render, action);
+            }
+            Ok(describe(views))
+        }
+        Command::Open(path) => {
+            // Going back to the main menu must not get around the lock
+            if ui::lock::is_locked() {
+                return Err("locked".into());
+            }
+            while views.len() > 1 {
+                views.apply(ViewResult::Pop);
+            }
+            for segment in path {
+                let view = views.top_mut().ok_or("no view")?;
+                let target = view
+                    .labels()
+                    .iter()
+                    .position(|label| label.trim().eq_ignore_ascii_case(segment))
+                    .ok_or_else(|| format!("no item {:?} in {:?}", segment, view.title()))?;
+                let current = view.selected().unwrap_or(0);
+                let step = if target > current {
+                    Action::Down
+                } else {
+                    Action::Up
+                };
+                for _ in 0..target.abs_diff(current) {
+                    dispatch(views, render, step);
+                }
+                dispatch(views, render, Action::Select);
+            }
+            Ok(describe(views))
+        }
+        Command::Dump => {
+            // Drawn afresh, earlier commands may have changed the screen
+            draw(render, views, Instant::now());
+            Ok(control::base64(&screenshot::encode(render)))
+        }
+    }
+}
+
+/// Locks and brings up the clock face once their timeouts pass, returns
+/// whether anything was pushed
+fn idle(views: &mut ViewStack, last_input: Instant, now: Instant) -> bool {
+    let lock = ui::lock::deadline(last_input).is_some_and(|t| now >= t);
+    let clock = ui::clockface::deadline(last_input).is_some_and(|t| now >= t);
+    if lock {
+        // The clock face stays on top of the lock screen
+        let covered = ui::clockface::is_showing();
+        if covered {
+            views.apply(ViewResult::Pop);
+        }
+        views.apply(ViewResult::Push(Box::new(LockView::new())));
+        if covered {
+            views.apply(ViewResult::Push(Box::new(ClockFace)));
+        }
+    }
+    if clock {
+        views.apply(ViewResult::Push(Box::new(ClockFace)));
+    }
+    lock || clock
 }
 
 fn run() -> std::io::Result<()> {
     let mut platform = Platform::new()?;
+    settings::load();
+    ui::font::load();
+    seed_inbox();
     let mut render = RenderBuffer::default_resolution();
-    let mut views: Vec<Box<dyn View>> = vec![Box::new(create_main_menu())];
-    let status_bar = StatusBar::default();
+    apps::register_builtin();
+    apps::register_external();
+    apps::start();
+    mqtt::start();
+    webhook::start();
+    let root: Box<dyn View> = match menus::load(screen) {
+        Some(menu) => Box::new(menu.quit_on_back()),
+        None => Box::new(Launcher::new()),
+    };
+    let mut views = ViewStack::new(root);
+    let status_bar = StatusBar;
+    let control = control::start();
+    let remote = rfb::start();
+
+    // Frames are only drawn on input, a view's wakeup or a `platform::wake`
+    let mut dirty = true;
+    let mut wakeup = None;
+    let start = Instant::now();
+    let mut last_input = start;
+    let mut recorder = Recorder::from_env(start);
+    let mut replay = Replay::from_env(start);
 
     while platform.is_open() && !views.is_empty() {
-        if let Some(action) = platform.poll() {
-            let result = views.last_mut().unwrap().handle(action);
-            match result {
-                ViewResult::None => {}
-                ViewResult::Push(view) => views.push(view),
-                ViewResult::Pop => {
-                    views.pop();
-                }
-                ViewResult::Exit => break,
+        // Keys pressed during a replay are drained and dropped
+        let polled = platform.poll();
+        let action = match &mut replay {
+            Some(replay) => replay.poll(Instant::now()),
+            None => polled.or_else(|| remote.try_recv().ok()),
+        };
+        if replay.as_ref().is_some_and(Replay::is_done) {
+            eprintln!("Replay finished, back to live input");
+            replay = None;
+        }
+
+        if let Some(action) = action {
+            last_input = Instant::now();
+            if let Some(recorder) = &mut recorder {
+                recorder.record(action, last_input);
             }
+            dispatch(&mut views, &render, action);
+            dirty = true;
         }
 
-        status_bar.render(&mut render);
-        if let Some(view) = views.last() {
-            view.render(&mut render);
+        while let Ok(request) = control.try_recv() {
+            if matches!(request.command, Command::Press(_) | Command::Open(_)) {
+                last_input = Instant::now();
+            }
+            let result = handle_control(&request.command, &mut views, &mut render);
+            request.reply(result);
+            dirty = true;
         }
-        platform.draw(&render);
-        platform.wait();
+        // Exit clears the stack
+        if views.is_empty() {
+            break;
+        }
+
+        let now = Instant::now();
+        if idle(&mut views, last_input, now) {
+            dirty = true;
+        }
+        if dirty || wakeup.is_some_and(|w| now >= w) {
+            let view_wakeup = views.top_mut().and_then(|view| view.tick(now));
+            let toast_wakeup = ui::toast::tick(now);
+            let bar_wakeup = status_bar.next_wakeup(now);
+            let lock_wakeup = ui::lock::deadline(last_input);
+            let clock_wakeup = ui::clockface::deadline(last_input);
+            wakeup = [
+                view_wakeup,
+                toast_wakeup,
+                lock_wakeup,
+                clock_wakeup,
+                Some(bar_wakeup),
+            ]
+            .into_iter()
+            .flatten()
+            .min();
+
+            draw(&mut render, &views, now);
+            platform.draw(&render);
+            rfb::publish(&render);
+        }
+
+        let replay_due = replay.as_ref().and_then(Replay::next_due);
+        dirty = platform.wait([wakeup, replay_due].into_iter().flatten().min());
     }
 
     Ok(())
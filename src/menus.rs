@@ -0,0 +1,163 @@
+//! Menu tree from `menu.json` in the config directory, letting a site
+//! change the menus without a new build
+//!
+//! The file holds the main menu, an object with a `title` and `items`.
+//! Every item has a `label` and one of:
+//!
+//! - `items`: a nested menu, titled by `title` or else the label
+//! - `screen`: an app such as `messages` or `settings`, or one of `apps`,
+//!   `inbox`, `test-alerts` and `exit`
+//! - `settings`: the settings under a section such as `display`
+//! - `run`: a shell command run in the background
+//! - `output`: a shell command whose output is shown as it runs, killed
+//!   after `timeout` seconds (30 by default, 0 for never) or on Back
+//!
+//! ```json
+//! {"title": "Main Menu", "items": [
+//!     {"label": "Messages", "screen": "messages"},
+//!     {"label": "Diagnostics", "items": [
+//!         {"label": "Ping gateway", "output": "ping -c 3 192.168.1.1"},
+//!         {"label": "Restart agent", "run": "/etc/init.d/agent restart"}
+//!     ]},
+//!     {"label": "Display", "settings": "display"}
+//! ]}
+//! ```
+
+use std::io;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::config;
+use crate::json::{self, Value};
+use crate::settings::SETTINGS;
+use crate::ui::{Menu, MenuAction, MenuItem, SettingsView, View};
+
+pub const FILE: &str = "menu.json";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Parsed menu, built into a fresh `Menu` each time it is opened
+struct MenuDef {
+    title: String,
+    items: Vec<(String, MenuAction)>,
+}
+
+impl MenuDef {
+    fn build(&self) -> Menu {
+        let items = self
+            .items
+            .iter()
+            .map(|(label, action)| MenuItem {
+                label: label.clone(),
+                action: action.clone(),
+            })
+            .collect();
+        Menu::new(self.title.clone(), items)
+    }
+}
+
+/// Main menu from the menu file, `None` to fall back to the built-in one
+/// when there is no file or it has errors. `screen` resolves `screen`
+/// items by name.
+pub fn load(screen: fn(&str) -> Option<MenuAction>) -> Option<Menu> {
+    let path = config::path(FILE);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            eprintln!("Error reading {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    let parsed = json::parse(&text)
+        .map_err(|e| e.to_string())
+        .and_then(|value| parse_menu(&value, "Main Menu", screen));
+    match parsed {
+        Ok(menu) => Some(menu.build()),
+        Err(e) => {
+            eprintln!("Error in {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn parse_menu(
+    value: &Value,
+    default_title: &str,
+    screen: fn(&str) -> Option<MenuAction>,
+) -> Result<MenuDef, String> {
+    let title = match value.get("title") {
+        Some(title) => title
+            .as_str()
+            .ok_or_else(|| format!("title of {:?} is not a string", default_title))?,
+        None => default_title,
+    };
+    let items = value
+        .get("items")
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("menu {:?} has no items list", title))?;
+    let items = items
+        .iter()
+        .map(|item| parse_item(item, screen))
+        .collect::<Result<_, _>>()?;
+    Ok(MenuDef {
+        title: title.to_string(),
+        items,
+    })
+}
+
+fn parse_item(
+    item: &Value,
+    screen: fn(&str) -> Option<MenuAction>,
+) -> Result<(String, MenuAction), String> {
+    let label = item
+        .get("label")
+        .and_then(Value::as_str)
+        .ok_or("menu item without a label")?
+        .to_string();
+    let text = |key: &str| -> Result<Option<String>, String> {
+        match item.get(key) {
+            Some(value) => value
+                .as_str()
+                .map(|s| Some(s.to_string()))
+                .ok_or_else(|| format!("{} of {:?} is not a string", key, label)),
+            None => Ok(None),
+        }
+    };
+
+    let action = if item.get("items").is_some() {
+        let menu = Rc::new(parse_menu(item, &label, screen)?);
+        MenuAction::Build(Rc::new(move || Box::new(menu.build()) as Box<dyn View>))
+    } else if let Some(name) = text("screen")? {
+        screen(&name).ok_or_else(|| format!("{:?} opens unknown screen {:?}", label, name))?
+    } else if let Some(section) = text("settings")? {
+        let prefix = format!("{}.", section);
+        if !SETTINGS.iter().any(|s| s.key.starts_with(&prefix)) {
+            return Err(format!("{:?} opens unknown settings {:?}", label, section));
+        }
+        let title = label.clone();
+        MenuAction::Build(Rc::new(move || {
+            Box::new(SettingsView::new(title.clone(), &section)) as Box<dyn View>
+        }))
+    } else if let Some(command) = text("run")? {
+        MenuAction::Shell(command)
+    } else if let Some(command) = text("output")? {
+        let timeout = match item.get("timeout") {
+            Some(value) => {
+                match value
+                    .as_f64()
+                    .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                {
+                    Some(timeout) if timeout.is_zero() => None,
+                    Some(timeout) => Some(timeout),
+                    None => return Err(format!("timeout of {:?} is not in seconds", label)),
+                }
+            }
+            None => Some(DEFAULT_TIMEOUT),
+        };
+        MenuAction::Output { command, timeout }
+    } else {
+        return Err(format!("{:?} has no action", label));
+    };
+    Ok((label, action))
+}
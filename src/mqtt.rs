@@ -0,0 +1,609 @@
+//! MQTT 3.1.1 client turning published alerts into inbox messages, enabled
+//! by `mqtt.conf` in the config directory
+//!
+//! ```text
+//! host = broker.lan
+//! port = 1883
+//! # Defaults to openpager- and the machine id, or the hostname
+//! client_id = pager-1
+//! username = pager
+//! password = secret
+//! keepalive = 60
+//!
+//! [topics]
+//! alerts/# = normal
+//! backups/+/status = low
+//! ```
+//!
+//! Each topic filter is subscribed to with QoS 1. Payloads are read by
+//! `inbox::parse_payload`. Without a priority in the payload a topic level
+//! named after one, as in `alerts/critical/db`, is used, and otherwise the
+//! priority of the filter that matched.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config;
+use crate::inbox;
+use crate::platform::{self, Priority};
+
+pub const FILE: &str = "mqtt.conf";
+
+const DEFAULT_PORT: u16 = 1883;
+const DEFAULT_KEEPALIVE: u16 = 60;
+
+/// Reconnect delays, doubling after every failed attempt
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Largest packet read, alerts are a line of text and anything bigger is
+/// not worth the memory
+const MAX_PACKET: usize = 64 * 1024;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often the connection wakes up to see if a ping is due
+const TICK: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// No `mqtt.conf`
+    Off,
+    Connecting,
+    Connected,
+    /// Waiting to try again
+    Disconnected,
+}
+
+static STATE: Mutex<State> = Mutex::new(State::Off);
+
+pub fn state() -> State {
+    *STATE.lock().unwrap()
+}
+
+fn set_state(state: State) {
+    let mut current = STATE.lock().unwrap();
+    if *current != state {
+        *current = state;
+        // The status bar shows it
+        platform::wake();
+    }
+}
+
+struct Config {
+    host: String,
+    port: u16,
+    client_id: String,
+    username: Option<String>,
+    password: Option<String>,
+    keepalive: u16,
+    /// Filters with the priority for messages matching them
+    topics: Vec<(String, Priority)>,
+}
+
+impl Config {
+    fn load() -> Option<Self> {
+        let path = config::path(FILE);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                eprintln!("Error reading {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        match Self::parse(&text) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                eprintln!("Error in {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut config = Config {
+            host: String::new(),
+            port: DEFAULT_PORT,
+            client_id: default_client_id(),
+            username: None,
+            password: None,
+            keepalive: DEFAULT_KEEPALIVE,
+            topics: Vec::new(),
+        };
+        for (key, value) in config::parse(text) {
+            if let Some(filter) = key.strip_prefix("topics.") {
+                let priority = Priority::from_name(&value)
+                    .ok_or_else(|| format!("unknown priority {:?} for {}", value, filter))?;
+                config.topics.push((filter.to_string(), priority));
+                continue;
+            }
+            match key.as_str() {
+                "host" => config.host = value,
+                "port" => config.port = value.parse().map_err(|_| "bad port")?,
+                "client_id" => config.client_id = value,
+                "username" => config.username = Some(value),
+                "password" => config.password = Some(value),
+                "keepalive" => config.keepalive = value.parse().map_err(|_| "bad keepalive")?,
+                _ => eprintln!("Ignoring unknown MQTT setting {}", key),
+            }
+        }
+        if config.host.is_empty() {
+            return Err("no host".into());
+        }
+        if config.topics.is_empty() {
+            return Err("no topics".into());
+        }
+        Ok(config)
+    }
+
+    /// Priority of the first filter matching `topic`
+    fn priority(&self, topic: &str) -> Priority {
+        self.topics
+            .iter()
+            .find(|(filter, _)| matches(filter, topic))
+            .map_or(Priority::Normal, |(_, priority)| *priority)
+    }
+}
+
+/// Stays the same across restarts so the broker keeps our session, and
+/// differs between units, which often share a hostname
+fn default_client_id() -> String {
+    let machine_id = std::fs::read_to_string("/etc/machine-id").unwrap_or_default();
+    let machine_id = machine_id.trim();
+    if !machine_id.is_empty() {
+        // Client ids up to 23 characters work with every broker
+        let short: String = machine_id.chars().take(12).collect();
+        return format!("openpager-{}", short);
+    }
+    let mut name = [0u8; 64];
+    unsafe { libc::gethostname(name.as_mut_ptr() as *mut libc::c_char, name.len()) };
+    let end = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+    format!("openpager-{}", String::from_utf8_lossy(&name[..end]))
+}
+
+/// Connects in the background when `mqtt.conf` is there, reconnecting
+/// whenever the connection drops
+pub fn start() {
+    let Some(config) = Config::load() else {
+        return;
+    };
+    set_state(State::Connecting);
+    thread::spawn(move || {
+        let mut backoff = MIN_BACKOFF;
+        loop {
+            set_state(State::Connecting);
+            if let Err(e) = session(&config, &mut backoff) {
+                eprintln!("MQTT connection to {}: {}", config.host, e);
+            }
+            set_state(State::Disconnected);
+            thread::sleep(backoff);
+            backoff = next_backoff(backoff);
+        }
+    });
+}
+
+fn next_backoff(backoff: Duration) -> Duration {
+    (backoff * 2).min(MAX_BACKOFF)
+}
+
+/// One connection from CONNECT until it fails, resets `backoff` once the
+/// broker has accepted us
+fn session(config: &Config, backoff: &mut Duration) -> io::Result<()> {
+    let address = (config.host.as_str(), config.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::other("host not found"))?;
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+
+    stream.write_all(&connect_packet(config))?;
+    let (header, body) = read_packet(&mut stream)?;
+    if header >> 4 != CONNACK || body.len() < 2 {
+        return Err(io::Error::other("expected CONNACK"));
+    }
+    if body[1] != 0 {
+        return Err(io::Error::other(format!(
+            "broker refused the connection: {}",
+            connack_reason(body[1])
+        )));
+    }
+
+    stream.write_all(&subscribe_packet(&config.topics))?;
+    set_state(State::Connected);
+    *backoff = MIN_BACKOFF;
+
+    let keepalive = Duration::from_secs(config.keepalive as u64);
+    let mut last_sent = Instant::now();
+    let mut ping_sent: Option<Instant> = None;
+    stream.set_read_timeout(Some(TICK))?;
+    loop {
+        match wait_for_packet(&mut stream)? {
+            Some((header, body)) => match header >> 4 {
+                PUBLISH => {
+                    if let Some(id) = receive_publish(config, header, &body) {
+                        stream.write_all(&[PUBACK << 4, 2, (id >> 8) as u8, id as u8])?;
+                        last_sent = Instant::now();
+                    }
+                }
+                SUBACK => {
+                    for (code, (filter, _)) in body.iter().skip(2).zip(&config.topics) {
+                        if *code == 0x80 {
+                            eprintln!("MQTT broker refused the subscription to {}", filter);
+                        }
+                    }
+                }
+                PINGRESP => ping_sent = None,
+                _ => {}
+            },
+            None => {
+                if ping_sent.is_some_and(|sent| sent.elapsed() >= keepalive) {
+                    return Err(io::Error::other("broker stopped answering"));
+                }
+            }
+        }
+        // The broker drops us after one and a half keepalives of silence
+        if keepalive > Duration::ZERO && ping_sent.is_none() && last_sent.elapsed() >= keepalive {
+            stream.write_all(&[PINGREQ << 4, 0])?;
+            last_sent = Instant::now();
+            ping_sent = Some(last_sent);
+        }
+    }
+}
+
+/// Puts a PUBLISH in the inbox, returns its packet id if it wants a PUBACK
+fn receive_publish(config: &Config, header: u8, body: &[u8]) -> Option<u16> {
+    let qos = (header >> 1) & 0x03;
+    let retained = header & 0x01 != 0;
+    let topic_len = u16::from_be_bytes([*body.first()?, *body.get(1)?]) as usize;
+    let topic = String::from_utf8_lossy(body.get(2..2 + topic_len)?);
+    let mut rest = &body[2 + topic_len..];
+    let id = if qos > 0 {
+        let id = u16::from_be_bytes([*rest.first()?, *rest.get(1)?]);
+        rest = &rest[2..];
+        Some(id)
+    } else {
+        None
+    };
+    // Retained messages are replayed on every subscribe, only the first
+    // copy is news
+    if let Some(incoming) = message(config, &topic, rest)
+        && !(retained && inbox::contains(&incoming))
+    {
+        inbox::deliver(incoming);
+    }
+    id
+}
+
+/// Message for a payload published on `topic`
+fn message(config: &Config, topic: &str, payload: &[u8]) -> Option<inbox::Incoming> {
+    let mut incoming = inbox::parse_payload(&String::from_utf8_lossy(payload))?;
+    let priority = incoming
+        .priority
+        .or_else(|| topic.split('/').find_map(Priority::from_name))
+        .unwrap_or_else(|| config.priority(topic));
+    incoming.priority = Some(priority);
+    Some(incoming)
+}
+
+/// Topic filter matching with `+` for one level and `#` for the rest
+fn matches(filter: &str, topic: &str) -> bool {
+    let mut topic_levels = topic.split('/');
+    for level in filter.split('/') {
+        match (level, topic_levels.next()) {
+            ("#", _) => return true,
+            ("+", Some(_)) => {}
+            (level, Some(topic_level)) if level == topic_level => {}
+            _ => return false,
+        }
+    }
+    topic_levels.next().is_none()
+}
+
+const CONNACK: u8 = 2;
+const PUBLISH: u8 = 3;
+const PUBACK: u8 = 4;
+const SUBSCRIBE: u8 = 8;
+const SUBACK: u8 = 9;
+const PINGREQ: u8 = 12;
+const PINGRESP: u8 = 13;
+
+fn connack_reason(code: u8) -> &'static str {
+    match code {
+        1 => "unacceptable protocol version",
+        2 => "client id rejected",
+        3 => "server unavailable",
+        4 => "bad user name or password",
+        5 => "not authorized",
+        _ => "unknown reason",
+    }
+}
+
+fn connect_packet(config: &Config) -> Vec<u8> {
+    // No clean session, the broker keeps what arrives while we are away
+    let mut flags = 0;
+    if config.username.is_some() {
+        flags |= 0x80;
+    }
+    if config.password.is_some() {
+        flags |= 0x40;
+    }
+    let mut body = Vec::new();
+    put_string(&mut body, "MQTT");
+    body.push(4); // Protocol level 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&config.keepalive.to_be_bytes());
+    put_string(&mut body, &config.client_id);
+    for field in [&config.username, &config.password].into_iter().flatten() {
+        put_string(&mut body, field);
+    }
+    packet(1 << 4, &body)
+}
+
+fn subscribe_packet(topics: &[(String, Priority)]) -> Vec<u8> {
+    let mut body = vec![0, 1]; // Packet id
+    for (filter, _) in topics {
+        put_string(&mut body, filter);
+        body.push(1); // QoS 1
+    }
+    packet(SUBSCRIBE << 4 | 0x02, &body)
+}
+
+fn put_string(out: &mut Vec<u8>, text: &str) {
+    out.extend_from_slice(&(text.len() as u16).to_be_bytes());
+    out.extend_from_slice(text.as_bytes());
+}
+
+/// Fixed header with the remaining length, then `body`
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = vec![header];
+    let mut len = body.len();
+    loop {
+        let byte = (len % 128) as u8;
+        len /= 128;
+        if len == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+    out.extend_from_slice(body);
+    out
+}
+
+/// Next packet, `None` if nothing arrived within the read timeout
+fn wait_for_packet(stream: &mut TcpStream) -> io::Result<Option<(u8, Vec<u8>)>> {
+    let mut header = [0u8; 1];
+    match stream.read(&mut header) {
+        Ok(0) => Err(io::Error::other("broker closed the connection")),
+        Ok(_) => read_body(stream, header[0]).map(Some),
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+            ) =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn read_packet(stream: &mut TcpStream) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 1];
+    stream.read_exact(&mut header)?;
+    read_body(stream, header[0])
+}
+
+fn read_body(stream: &mut TcpStream, header: u8) -> io::Result<(u8, Vec<u8>)> {
+    // The rest of a packet follows its first byte closely, read it even
+    // if the read timeout is short
+    let timeout = stream.read_timeout()?;
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+    let mut len = 0usize;
+    for shift in (0..28).step_by(7) {
+        let mut byte = [0u8; 1];
+        stream.read_exact(&mut byte)?;
+        len |= ((byte[0] & 0x7F) as usize) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+    }
+    if len > MAX_PACKET {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} byte packet is too large", len),
+        ));
+    }
+    let mut body = vec![0u8; len];
+    stream.read_exact(&mut body)?;
+    stream.set_read_timeout(timeout)?;
+    Ok((header, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+
+    fn config(port: u16, topics: &[(&str, Priority)]) -> Config {
+        Config {
+            host: "127.0.0.1".into(),
+            port,
+            client_id: "pager-test".into(),
+            username: Some("pager".into()),
+            password: None,
+            keepalive: 60,
+            topics: topics
+                .iter()
+                .map(|(filter, priority)| (filter.to_string(), *priority))
+                .collect(),
+        }
+    }
+
+    /// Broker on a free local port, running `script` on the first
+    /// connection and handing back what it returns
+    fn broker<T: Send + 'static>(
+        script: impl FnOnce(TcpStream) -> T + Send + 'static,
+    ) -> (u16, thread::JoinHandle<T>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            stream.set_read_timeout(Some(CONNECT_TIMEOUT)).unwrap();
+            script(stream)
+        });
+        (port, handle)
+    }
+
+    fn publish(topic: &str, id: Option<u16>, payload: &str, flags: u8) -> Vec<u8> {
+        let mut body = Vec::new();
+        put_string(&mut body, topic);
+        if let Some(id) = id {
+            body.extend_from_slice(&id.to_be_bytes());
+        }
+        body.extend_from_slice(payload.as_bytes());
+        packet(PUBLISH << 4 | flags, &body)
+    }
+
+    fn find(subject: &str) -> Option<inbox::Message> {
+        inbox::messages().into_iter().find(|m| m.subject == subject)
+    }
+
+    #[test]
+    fn session_with_broker() {
+        let (port, broker) = broker(|mut stream| {
+            let connect = read_packet(&mut stream).unwrap();
+            stream.write_all(&[CONNACK << 4, 2, 0, 0]).unwrap();
+            let subscribe = read_packet(&mut stream).unwrap();
+            stream.write_all(&[SUBACK << 4, 4, 0, 1, 1, 0x80]).unwrap();
+
+            stream
+                .write_all(&publish("mqtt-test/qos0", None, "MQTT test QoS 0", 0))
+                .unwrap();
+            stream
+                .write_all(&publish("mqtt-test/old", None, "MQTT test retained", 0x01))
+                .unwrap();
+            stream
+                .write_all(&publish("mqtt-test/old", None, "MQTT test retained", 0x01))
+                .unwrap();
+            let payload = r#"{"subject": "MQTT test QoS 1", "priority": "high"}"#;
+            stream
+                .write_all(&publish("mqtt-test/qos1", Some(0x1234), payload, 0x02))
+                .unwrap();
+            let mut puback = [0u8; 4];
+            stream.read_exact(&mut puback).unwrap();
+            (connect, subscribe, puback)
+        });
+        let config = config(
+            port,
+            &[("mqtt-test/#", Priority::Low), ("other", Priority::High)],
+        );
+        let mut backoff = MAX_BACKOFF;
+        let error = session(&config, &mut backoff).unwrap_err();
+        assert_eq!(error.to_string(), "broker closed the connection");
+        assert_eq!(backoff, MIN_BACKOFF);
+
+        let (connect, subscribe, puback) = broker.join().unwrap();
+        assert_eq!(connect.0, 0x10);
+        assert_eq!(
+            connect.1,
+            b"\0\x04MQTT\x04\x80\0\x3c\0\x0apager-test\0\x05pager"
+        );
+        assert_eq!(subscribe.0, 0x82);
+        assert_eq!(subscribe.1, b"\0\x01\0\x0bmqtt-test/#\x01\0\x05other\x01");
+        assert_eq!(puback, [PUBACK << 4, 2, 0x12, 0x34]);
+
+        let qos0 = find("MQTT test QoS 0").expect("QoS 0 message");
+        assert_eq!(qos0.priority, Priority::Low);
+        let qos1 = find("MQTT test QoS 1").expect("QoS 1 message");
+        assert_eq!(qos1.priority, Priority::High);
+        let retained = inbox::messages()
+            .into_iter()
+            .filter(|m| m.subject == "MQTT test retained")
+            .count();
+        assert_eq!(retained, 1);
+    }
+
+    #[test]
+    fn refused_connection() {
+        let (port, broker) = broker(|mut stream| {
+            read_packet(&mut stream).unwrap();
+            stream.write_all(&[CONNACK << 4, 2, 0, 5]).unwrap();
+        });
+        let mut backoff = MAX_BACKOFF;
+        let error = session(&config(port, &[("a", Priority::Normal)]), &mut backoff).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "broker refused the connection: not authorized"
+        );
+        // Only an accepted connection starts the delays over
+        assert_eq!(backoff, MAX_BACKOFF);
+        broker.join().unwrap();
+    }
+
+    #[test]
+    fn oversized_packet() {
+        let (port, broker) = broker(|mut stream| {
+            // A remaining length of 2 MiB, with no body behind it
+            stream
+                .write_all(&[PUBLISH << 4, 0x80, 0x80, 0x80, 0x01])
+                .unwrap();
+        });
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        broker.join().unwrap();
+        let error = read_packet(&mut stream).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn topic_filters() {
+        assert!(matches("alerts/db", "alerts/db"));
+        assert!(!matches("alerts/db", "alerts/web"));
+        assert!(!matches("alerts/db", "alerts/db/disk"));
+        assert!(matches("alerts/+/status", "alerts/db/status"));
+        assert!(!matches("alerts/+/status", "alerts/status"));
+        assert!(matches("+", "alerts"));
+        assert!(!matches("+", "alerts/db"));
+        assert!(matches("alerts/#", "alerts/db/disk"));
+        assert!(matches("alerts/#", "alerts"));
+        assert!(matches("#", "anything/at/all"));
+        assert!(!matches("alerts/#", "backups/db"));
+    }
+
+    #[test]
+    fn message_priority() {
+        let config = config(0, &[("alerts/#", Priority::Low), ("+/db", Priority::High)]);
+        let priority = |topic: &str, payload: &str| {
+            message(&config, topic, payload.as_bytes())
+                .unwrap()
+                .priority
+                .unwrap()
+        };
+        // The payload wins, then a topic level, then the filter
+        let payload = r#"{"subject": "disk full", "priority": "normal"}"#;
+        assert_eq!(priority("alerts/critical/db", payload), Priority::Normal);
+        assert_eq!(
+            priority("alerts/critical/db", "disk full"),
+            Priority::Critical
+        );
+        assert_eq!(priority("alerts/db", "disk full"), Priority::Low);
+        assert_eq!(priority("backups/db", "disk full"), Priority::High);
+        assert_eq!(priority("unmatched", "disk full"), Priority::Normal);
+        assert!(message(&config, "alerts/db", b"  ").is_none());
+    }
+
+    #[test]
+    fn backoff() {
+        let mut backoff = MIN_BACKOFF;
+        let mut delays = Vec::new();
+        for _ in 0..8 {
+            delays.push(backoff.as_secs());
+            backoff = next_backoff(backoff);
+        }
+        assert_eq!(delays, [1, 2, 4, 8, 16, 32, 60, 60]);
+    }
+}
@@ -0,0 +1,45 @@
+//! Wall clock in the device's local time zone
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl LocalTime {
+    pub fn minutes(self) -> u16 {
+        self.hour as u16 * 60 + self.minute as u16
+    }
+}
+
+fn now() -> libc::tm {
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        libc::localtime_r(&now, &mut tm);
+    }
+    tm
+}
+
+/// Current time, honoring `TZ` and `/etc/localtime` through libc
+pub fn local_time() -> LocalTime {
+    let tm = now();
+    LocalTime {
+        hour: tm.tm_hour as u8,
+        minute: tm.tm_min as u8,
+    }
+}
+
+/// Local date and time as `YYYYMMDD-HHMMSS`, for file names
+pub fn timestamp() -> String {
+    let tm = now();
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
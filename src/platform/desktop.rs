@@ -1,15 +1,45 @@
+use embedded_graphics::pixelcolor::RgbColor;
 use minifb::{Key, KeyRepeat, Window, WindowOptions};
 use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
 
-use super::{Action, RenderBuffer};
+use super::{Action, RenderBuffer, leds};
 use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
 
+/// Strip below the display holding the virtual status LED
+const LED_STRIP_HEIGHT: u32 = 14;
+const LED_STRIP_COLOR: u32 = 0x101010;
+const LED_OFF_COLOR: u32 = 0x303030;
+const LED_RADIUS: i32 = 4;
+
+static BRIGHTNESS: AtomicU8 = AtomicU8::new(100);
+static WOKEN: AtomicBool = AtomicBool::new(false);
+
+/// Event pumping interval while waiting, minifb has no blocking wait
+const PUMP_INTERVAL: Duration = Duration::from_millis(16);
+
 pub fn read_battery() -> (u8, bool) {
     (100, false)
 }
 
+/// Dims the window contents to mimic the backlight
+pub fn set_brightness(percent: u8) {
+    BRIGHTNESS.store(percent.min(100), Ordering::Relaxed);
+}
+
+/// The desktop build leaves the host's name alone
+pub fn set_hostname(_name: &str) {}
+
+/// Interrupts a pending `wait` so the UI redraws, callable from any thread
+pub fn wake() {
+    WOKEN.store(true, Ordering::Relaxed);
+}
+
 pub struct DesktopPlatform {
     window: Window,
+    frame: Vec<u32>,
+    led: Option<embedded_graphics::pixelcolor::Rgb888>,
 }
 
 impl DesktopPlatform {
@@ -18,7 +48,7 @@ impl DesktopPlatform {
         let window = Window::new(
             "OpenPager",
             DISPLAY_WIDTH as usize * scale,
-            DISPLAY_HEIGHT as usize * scale,
+            (DISPLAY_HEIGHT + LED_STRIP_HEIGHT) as usize * scale,
             WindowOptions {
                 scale: minifb::Scale::X1,
                 scale_mode: minifb::ScaleMode::AspectRatioStretch,
@@ -27,7 +57,11 @@ impl DesktopPlatform {
         )
         .map_err(io::Error::other)?;
 
-        Ok(Self { window })
+        Ok(Self {
+            window,
+            frame: vec![0; (DISPLAY_WIDTH * (DISPLAY_HEIGHT + LED_STRIP_HEIGHT)) as usize],
+            led: None,
+        })
     }
 
     pub fn is_open(&self) -> bool {
@@ -41,6 +75,8 @@ impl DesktopPlatform {
                 Key::Down => return Some(Action::Down),
                 Key::Enter => return Some(Action::Select),
                 Key::Escape => return Some(Action::Back),
+                Key::P => return Some(Action::Power),
+                Key::F12 => return Some(Action::Screenshot),
                 _ => {}
             }
         }
@@ -48,14 +84,73 @@ impl DesktopPlatform {
     }
 
     pub fn draw(&mut self, render: &RenderBuffer) {
+        let display = render.pixels_raw();
+        let brightness = BRIGHTNESS.load(Ordering::Relaxed) as u32;
+        if brightness >= 100 {
+            self.frame[..display.len()].copy_from_slice(display);
+        } else {
+            for (dst, &src) in self.frame.iter_mut().zip(display) {
+                let r = ((src >> 16) & 0xFF) * brightness / 100;
+                let g = ((src >> 8) & 0xFF) * brightness / 100;
+                let b = (src & 0xFF) * brightness / 100;
+                *dst = r << 16 | g << 8 | b;
+            }
+        }
+        self.draw_led();
+        self.present();
+    }
+
+    fn present(&mut self) {
         let _ = self.window.update_with_buffer(
-            render.pixels_raw(),
+            &self.frame,
             DISPLAY_WIDTH as usize,
-            DISPLAY_HEIGHT as usize,
+            (DISPLAY_HEIGHT + LED_STRIP_HEIGHT) as usize,
         );
     }
 
-    pub fn wait(&self) {
-        std::thread::sleep(std::time::Duration::from_millis(16));
+    fn draw_led(&mut self) {
+        let strip = (DISPLAY_WIDTH * DISPLAY_HEIGHT) as usize;
+        self.frame[strip..].fill(LED_STRIP_COLOR);
+
+        self.led = leds::lit();
+        let color = self
+            .led
+            .map(|c| (c.r() as u32) << 16 | (c.g() as u32) << 8 | c.b() as u32)
+            .unwrap_or(LED_OFF_COLOR);
+        let cx = DISPLAY_WIDTH as i32 - 12;
+        let cy = (DISPLAY_HEIGHT + LED_STRIP_HEIGHT / 2) as i32;
+        for y in -LED_RADIUS..=LED_RADIUS {
+            for x in -LED_RADIUS..=LED_RADIUS {
+                if x * x + y * y <= LED_RADIUS * LED_RADIUS {
+                    let idx = ((cy + y) * DISPLAY_WIDTH as i32 + cx + x) as usize;
+                    self.frame[idx] = color;
+                }
+            }
+        }
+    }
+
+    /// Sleeps until a key is pressed, `wake` is called or `deadline` passes,
+    /// returns whether `wake` was the reason
+    pub fn wait(&mut self, deadline: Option<Instant>) -> bool {
+        loop {
+            if WOKEN.swap(false, Ordering::Relaxed) {
+                return true;
+            }
+            if deadline.is_some_and(|d| Instant::now() >= d)
+                || !self.window.is_open()
+                || !self.window.get_keys_pressed(KeyRepeat::No).is_empty()
+            {
+                return false;
+            }
+
+            std::thread::sleep(PUMP_INTERVAL);
+            // Keep the virtual LED blinking while the display is idle
+            if leds::lit() != self.led {
+                self.draw_led();
+                self.present();
+            } else {
+                self.window.update();
+            }
+        }
     }
 }
@@ -39,8 +39,9 @@ struct InputEvent {
     value: i32,
 }
 
+use std::sync::OnceLock;
 use std::sync::atomic::{AtomicU8, AtomicBool, AtomicU32, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 static BATTERY_CAPACITY: AtomicU8 = AtomicU8::new(100);
 static BATTERY_CHARGING: AtomicBool = AtomicBool::new(false);
@@ -75,12 +76,74 @@ pub fn read_battery() -> (u8, bool) {
     )
 }
 
+const BACKLIGHT_PATH: &str = "/sys/class/backlight";
+
+/// Scales the first backlight found to `percent` of its maximum
+pub fn set_brightness(percent: u8) {
+    let Some(backlight) = std::fs::read_dir(BACKLIGHT_PATH)
+        .ok()
+        .and_then(|mut entries| entries.next())
+        .and_then(|entry| entry.ok())
+    else {
+        return;
+    };
+    let path = backlight.path();
+    let max: u32 = std::fs::read_to_string(path.join("max_brightness"))
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(255);
+    let value = max * percent.min(100) as u32 / 100;
+    let _ = std::fs::write(path.join("brightness"), value.to_string());
+}
+
+/// Empty keeps the current hostname
+pub fn set_hostname(name: &str) {
+    if name.is_empty() {
+        return;
+    }
+    let result = unsafe { libc::sethostname(name.as_ptr() as *const libc::c_char, name.len()) };
+    if result != 0 {
+        eprintln!(
+            "Error setting hostname {:?}: {}",
+            name,
+            io::Error::last_os_error()
+        );
+    }
+}
+
+/// Self-pipe that lets other threads interrupt `wait`
+static WAKE_PIPE: OnceLock<[libc::c_int; 2]> = OnceLock::new();
+
+fn wake_pipe() -> [libc::c_int; 2] {
+    *WAKE_PIPE.get_or_init(|| {
+        let mut fds = [-1; 2];
+        unsafe {
+            libc::pipe(fds.as_mut_ptr());
+            for fd in fds {
+                let flags = libc::fcntl(fd, libc::F_GETFL);
+                libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
+            }
+        }
+        fds
+    })
+}
+
+/// Interrupts a pending `wait` so the UI redraws, callable from any thread
+pub fn wake() {
+    let byte = 1u8;
+    unsafe {
+        libc::write(wake_pipe()[1], &byte as *const u8 as *const libc::c_void, 1);
+    }
+}
+
 pub struct DevicePlatform {
     fb_file: File,
     fb_width: u32,
     fb_buffer: Vec<u16>,
     input_file: File,
     input_buf: [u8; INPUT_EVENT_SIZE],
+    /// Up and Down held, pressing both takes a screenshot
+    held: [bool; 2],
 }
 
 impl DevicePlatform {
@@ -111,6 +174,7 @@ impl DevicePlatform {
             fb_buffer,
             input_file,
             input_buf: [0u8; INPUT_EVENT_SIZE],
+            held: [false; 2],
         })
     }
 
@@ -122,12 +186,30 @@ impl DevicePlatform {
         // Drain events until we find an actionable one or buffer is empty
         while let Ok(INPUT_EVENT_SIZE) = self.input_file.read(&mut self.input_buf) {
             let event: InputEvent = unsafe { std::ptr::read(self.input_buf.as_ptr() as *const _) };
-            if event.type_ == EV_KEY && event.value == 1 {
+            if event.type_ != EV_KEY {
+                continue;
+            }
+            // Value 0 is a release, 1 a press and 2 a repeat
+            let arrow = match event.code {
+                KEY_UP => Some(0),
+                KEY_DOWN => Some(1),
+                _ => None,
+            };
+            if let Some(i) = arrow
+                && event.value != 2
+            {
+                self.held[i] = event.value == 1;
+                if event.value == 1 && self.held[1 - i] {
+                    return Some(Action::Screenshot);
+                }
+            }
+            if event.value == 1 {
                 let action = match event.code {
                     KEY_UP => Some(Action::Up),
                     KEY_DOWN => Some(Action::Down),
                     BTN_FORWARD => Some(Action::Select),
-                    BTN_BACK | KEY_POWER => Some(Action::Back),
+                    BTN_BACK => Some(Action::Back),
+                    KEY_POWER => Some(Action::Power),
                     _ => None,
                 };
                 if action.is_some() {
@@ -171,7 +253,37 @@ impl DevicePlatform {
         let _ = self.fb_file.write_all(bytes);
     }
 
-    pub fn wait(&self) {
-        std::thread::sleep(std::time::Duration::from_millis(10));
+    /// Sleeps until input arrives, `wake` is called or `deadline` passes,
+    /// returns whether `wake` was the reason
+    pub fn wait(&mut self, deadline: Option<Instant>) -> bool {
+        let timeout = deadline.map_or(-1, |deadline| {
+            let micros = deadline
+                .saturating_duration_since(Instant::now())
+                .as_micros();
+            micros.div_ceil(1000).min(libc::c_int::MAX as u128) as libc::c_int
+        });
+        let wake_fd = wake_pipe()[0];
+        let mut fds = [
+            libc::pollfd {
+                fd: self.input_file.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: wake_fd,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+        unsafe {
+            libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout);
+        }
+
+        let woken = fds[1].revents & libc::POLLIN != 0;
+        if woken {
+            let mut drain = [0u8; 64];
+            while unsafe { libc::read(wake_fd, drain.as_mut_ptr() as *mut libc::c_void, 64) } > 0 {}
+        }
+        woken
     }
 }
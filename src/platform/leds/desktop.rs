@@ -0,0 +1,38 @@
+use std::sync::Mutex;
+use std::time::Instant;
+
+use embedded_graphics::pixelcolor::Rgb888;
+
+use super::Blink;
+
+const RED: Rgb888 = Rgb888::new(0xFF, 0x30, 0x30);
+const GREEN: Rgb888 = Rgb888::new(0x30, 0xFF, 0x60);
+
+static CURRENT: Mutex<Option<(Blink, Instant)>> = Mutex::new(None);
+
+/// Virtual LED, drawn by the desktop window below the display
+pub struct Backend;
+
+impl Backend {
+    pub fn open() -> Self {
+        Self
+    }
+
+    pub fn set(&mut self, blink: Option<Blink>) {
+        let mut current = CURRENT.lock().unwrap();
+        if current.map(|(b, _)| b) != blink {
+            *current = blink.map(|b| (b, Instant::now()));
+        }
+    }
+}
+
+/// Color of the virtual LED at this moment, `None` while dark
+pub fn lit() -> Option<Rgb888> {
+    let (blink, since) = (*CURRENT.lock().unwrap())?;
+    let period = blink.on_ms + blink.off_ms;
+    let phase = (since.elapsed().as_millis() % period as u128) as u32;
+    if phase >= blink.on_ms {
+        return None;
+    }
+    Some(if blink.urgent { RED } else { GREEN })
+}
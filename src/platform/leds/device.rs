@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::Blink;
+
+const LEDS_PATH: &str = "/sys/class/leds";
+const COLORS: &[&str] = &["red", "green", "blue", "white", "amber"];
+const FUNCTIONS: &[&str] = &["status", "notification", "indicator"];
+
+/// Drives `/sys/class/leds/*` through the kernel timer trigger
+pub struct Backend {
+    urgent: Vec<PathBuf>,
+    normal: Vec<PathBuf>,
+    current: Option<Blink>,
+}
+
+impl Backend {
+    pub fn open() -> Self {
+        let mut urgent = Vec::new();
+        let mut normal = Vec::new();
+        if let Ok(entries) = fs::read_dir(LEDS_PATH) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_lowercase();
+                let usable = COLORS.iter().chain(FUNCTIONS).any(|k| name.contains(k));
+                if !usable {
+                    continue;
+                }
+                if name.contains("red") {
+                    urgent.push(entry.path());
+                } else {
+                    normal.push(entry.path());
+                }
+            }
+        }
+        // A single LED has to carry both patterns
+        if urgent.is_empty() {
+            urgent = normal.clone();
+        } else if normal.is_empty() {
+            normal = urgent.clone();
+        }
+
+        let backend = Self {
+            urgent,
+            normal,
+            current: None,
+        };
+        backend.all_off();
+        backend
+    }
+
+    pub fn set(&mut self, blink: Option<Blink>) {
+        if blink == self.current {
+            return;
+        }
+        self.current = blink;
+        self.all_off();
+        if let Some(blink) = blink {
+            let leds = if blink.urgent {
+                &self.urgent
+            } else {
+                &self.normal
+            };
+            for led in leds {
+                write(led, "trigger", "timer");
+                write(led, "delay_on", &blink.on_ms.to_string());
+                write(led, "delay_off", &blink.off_ms.to_string());
+            }
+        }
+    }
+
+    fn all_off(&self) {
+        for led in self.urgent.iter().chain(&self.normal) {
+            write(led, "trigger", "none");
+            write(led, "brightness", "0");
+        }
+    }
+}
+
+fn write(led: &Path, attr: &str, value: &str) {
+    let _ = fs::write(led.join(attr), value);
+}
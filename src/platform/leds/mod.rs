@@ -0,0 +1,48 @@
+//! Status LEDs, blinking per message priority
+
+use std::sync::Mutex;
+
+use super::Priority;
+
+#[cfg(target_arch = "mips")]
+mod device;
+#[cfg(target_arch = "mips")]
+use device::Backend;
+
+#[cfg(not(target_arch = "mips"))]
+mod desktop;
+#[cfg(not(target_arch = "mips"))]
+use desktop::Backend;
+#[cfg(not(target_arch = "mips"))]
+pub use desktop::lit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blink {
+    pub on_ms: u32,
+    pub off_ms: u32,
+    pub urgent: bool,
+}
+
+impl Priority {
+    pub fn blink(self) -> Blink {
+        let (on_ms, off_ms) = match self {
+            Priority::Low | Priority::Normal => (100, 2900),
+            Priority::High => (200, 800),
+            Priority::Critical => (150, 150),
+        };
+        Blink {
+            on_ms,
+            off_ms,
+            urgent: self >= Priority::High,
+        }
+    }
+}
+
+static LEDS: Mutex<Option<Backend>> = Mutex::new(None);
+
+/// Blinks for the highest pending priority, `None` turns the LEDs off
+pub fn show(priority: Option<Priority>) {
+    let mut leds = LEDS.lock().unwrap();
+    leds.get_or_insert_with(Backend::open)
+        .set(priority.map(Priority::blink));
+}
@@ -1,17 +1,21 @@
 //! Platform abstraction for display and input
 
+pub mod clock;
+pub mod leds;
+mod notifier;
 mod render;
+pub use notifier::{Priority, notify, set_alert_output};
 pub use render::RenderBuffer;
 
 #[cfg(target_arch = "mips")]
 mod device;
 #[cfg(target_arch = "mips")]
-pub use device::{read_battery, DevicePlatform as Platform};
+pub use device::{read_battery, set_brightness, set_hostname, wake, DevicePlatform as Platform};
 
 #[cfg(not(target_arch = "mips"))]
 mod desktop;
 #[cfg(not(target_arch = "mips"))]
-pub use desktop::{read_battery, DesktopPlatform as Platform};
+pub use desktop::{read_battery, set_brightness, set_hostname, wake, DesktopPlatform as Platform};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Action {
@@ -19,4 +23,35 @@ pub enum Action {
     Down,
     Select,
     Back,
+    /// Power key, locks the screen
+    Power,
+    /// Saves a screenshot, Up and Down together on the device
+    Screenshot,
+}
+
+impl Action {
+    const ALL: [Action; 6] = [
+        Action::Up,
+        Action::Down,
+        Action::Select,
+        Action::Back,
+        Action::Power,
+        Action::Screenshot,
+    ];
+
+    /// Lowercase name used in input logs and control commands
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Select => "select",
+            Action::Back => "back",
+            Action::Power => "power",
+            Action::Screenshot => "screenshot",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
 }
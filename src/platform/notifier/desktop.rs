@@ -0,0 +1,91 @@
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use super::Tone;
+
+const SAMPLE_RATE: u32 = 8000;
+const PLAYERS: &[&str] = &["paplay", "aplay", "afplay"];
+
+/// Renders patterns to a WAV file and hands it to a system player
+pub struct Backend {
+    path: PathBuf,
+    player: Option<&'static str>,
+}
+
+impl Backend {
+    pub fn open() -> Self {
+        Self {
+            path: std::env::temp_dir().join("openpager-alert.wav"),
+            player: PLAYERS.first().copied(),
+        }
+    }
+
+    pub fn play(&mut self, pattern: &[Tone]) {
+        let played = std::fs::write(&self.path, wav(pattern)).is_ok() && self.spawn_player();
+        if !played {
+            let tones: Vec<String> = pattern
+                .iter()
+                .map(|t| {
+                    let buzz = if t.vibrate { "+vib" } else { "" };
+                    format!("{}Hz/{}ms{}", t.freq, t.ms, buzz)
+                })
+                .collect();
+            eprintln!("alert: {}", tones.join(" "));
+        }
+    }
+
+    /// Tries players in order, remembering the first one that exists
+    fn spawn_player(&mut self) -> bool {
+        while let Some(player) = self.player {
+            let status = Command::new(player)
+                .arg(&self.path)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+            match status {
+                Ok(status) => return status.success(),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    let next = PLAYERS.iter().position(|p| *p == player).unwrap() + 1;
+                    self.player = PLAYERS.get(next).copied();
+                }
+                Err(_) => return false,
+            }
+        }
+        false
+    }
+}
+
+/// 8-bit mono PCM, square wave
+fn wav(pattern: &[Tone]) -> Vec<u8> {
+    let mut samples = Vec::new();
+    for tone in pattern {
+        let count = SAMPLE_RATE * tone.ms as u32 / 1000;
+        for i in 0..count {
+            let sample = if tone.freq == 0 {
+                0x80
+            } else if (i * tone.freq as u32 * 2 / SAMPLE_RATE).is_multiple_of(2) {
+                0xA0
+            } else {
+                0x60
+            };
+            samples.push(sample);
+        }
+    }
+
+    let mut out = Vec::with_capacity(44 + samples.len());
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + samples.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    out.extend_from_slice(&1u16.to_le_bytes()); // mono
+    out.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    out.extend_from_slice(&SAMPLE_RATE.to_le_bytes()); // byte rate
+    out.extend_from_slice(&1u16.to_le_bytes()); // block align
+    out.extend_from_slice(&8u16.to_le_bytes()); // bits per sample
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+    out.extend_from_slice(&samples);
+    out
+}
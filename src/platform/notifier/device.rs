@@ -0,0 +1,84 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::thread::sleep;
+use std::time::Duration;
+
+use super::Tone;
+
+const INPUT_EVENT_SIZE: usize = 16;
+const EV_SYN: u16 = 0x00;
+const EV_SND: u16 = 0x12;
+const SND_TONE: u16 = 0x02;
+const VIBRATOR_PATH: &str = "/sys/class/timed_output/vibrator/enable";
+
+/// Buzzer through an `EV_SND` capable input device, vibration through sysfs
+pub struct Backend {
+    buzzer: Option<File>,
+    vibrator: Option<File>,
+}
+
+impl Backend {
+    pub fn open() -> Self {
+        let vibrator = OpenOptions::new().write(true).open(VIBRATOR_PATH).ok();
+        Self {
+            buzzer: find_buzzer(),
+            vibrator,
+        }
+    }
+
+    pub fn play(&mut self, pattern: &[Tone]) {
+        for tone in pattern {
+            if tone.vibrate
+                && let Some(vibrator) = &mut self.vibrator
+            {
+                let _ = vibrator.write_all(tone.ms.to_string().as_bytes());
+            }
+            if tone.freq > 0 {
+                self.tone(tone.freq as i32);
+            }
+            sleep(Duration::from_millis(tone.ms as u64));
+            if tone.freq > 0 {
+                self.tone(0);
+            }
+        }
+    }
+
+    fn tone(&mut self, freq: i32) {
+        if let Some(buzzer) = &mut self.buzzer {
+            let _ = buzzer.write_all(&event(EV_SND, SND_TONE, freq));
+            let _ = buzzer.write_all(&event(EV_SYN, 0, 0));
+        }
+    }
+}
+
+/// Scans input devices for one advertising sound capabilities
+fn find_buzzer() -> Option<File> {
+    let entries = std::fs::read_dir("/sys/class/input").ok()?;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if !name.starts_with("event") {
+            continue;
+        }
+        let caps = entry.path().join("device/capabilities/snd");
+        let supported = std::fs::read_to_string(&caps)
+            .map(|s| s.trim() != "0")
+            .unwrap_or(false);
+        if supported {
+            let dev = Path::new("/dev/input").join(name.as_ref());
+            if let Ok(file) = OpenOptions::new().write(true).open(dev) {
+                return Some(file);
+            }
+        }
+    }
+    None
+}
+
+fn event(type_: u16, code: u16, value: i32) -> [u8; INPUT_EVENT_SIZE] {
+    let mut buf = [0u8; INPUT_EVENT_SIZE];
+    buf[8..10].copy_from_slice(&type_.to_ne_bytes());
+    buf[10..12].copy_from_slice(&code.to_ne_bytes());
+    buf[12..16].copy_from_slice(&value.to_ne_bytes());
+    buf
+}
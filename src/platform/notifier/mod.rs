@@ -0,0 +1,134 @@
+//! Audible and haptic alerts
+
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Sender, channel};
+
+#[cfg(target_arch = "mips")]
+mod device;
+#[cfg(target_arch = "mips")]
+use device::Backend;
+
+#[cfg(not(target_arch = "mips"))]
+mod desktop;
+#[cfg(not(target_arch = "mips"))]
+use desktop::Backend;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+    Critical,
+}
+
+/// One step of an alert pattern, a zero frequency is a pause
+#[derive(Debug, Clone, Copy)]
+pub struct Tone {
+    pub freq: u16,
+    pub ms: u16,
+    pub vibrate: bool,
+}
+
+const fn beep(freq: u16, ms: u16) -> Tone {
+    Tone {
+        freq,
+        ms,
+        vibrate: true,
+    }
+}
+
+const fn pause(ms: u16) -> Tone {
+    Tone {
+        freq: 0,
+        ms,
+        vibrate: false,
+    }
+}
+
+const LOW: &[Tone] = &[beep(1800, 60)];
+const NORMAL: &[Tone] = &[beep(2000, 100), pause(80), beep(2000, 100)];
+const HIGH: &[Tone] = &[
+    beep(2400, 120),
+    pause(60),
+    beep(2400, 120),
+    pause(60),
+    beep(2400, 120),
+];
+const CRITICAL: &[Tone] = &[
+    beep(2800, 250),
+    beep(2000, 250),
+    beep(2800, 250),
+    beep(2000, 250),
+    pause(300),
+    beep(2800, 250),
+    beep(2000, 250),
+    beep(2800, 250),
+    beep(2000, 250),
+];
+
+impl Priority {
+    /// From `low`, `normal`, `high` or `critical` in any case, as used by
+    /// message sources
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "low" => Some(Priority::Low),
+            "normal" => Some(Priority::Normal),
+            "high" => Some(Priority::High),
+            "critical" => Some(Priority::Critical),
+            _ => None,
+        }
+    }
+
+    pub fn pattern(self) -> &'static [Tone] {
+        match self {
+            Priority::Low => LOW,
+            Priority::Normal => NORMAL,
+            Priority::High => HIGH,
+            Priority::Critical => CRITICAL,
+        }
+    }
+}
+
+static QUEUE: OnceLock<Sender<Priority>> = OnceLock::new();
+static SOUND: AtomicBool = AtomicBool::new(true);
+static VIBRATE: AtomicBool = AtomicBool::new(true);
+
+/// Chooses which outputs alert patterns use
+pub fn set_alert_output(sound: bool, vibrate: bool) {
+    SOUND.store(sound, Ordering::Relaxed);
+    VIBRATE.store(vibrate, Ordering::Relaxed);
+}
+
+/// Plays the pattern for `priority` on a background thread, never blocks
+pub fn notify(priority: Priority) {
+    // Tests deliver messages too, they stay quiet
+    if cfg!(test) {
+        return;
+    }
+    let queue = QUEUE.get_or_init(|| {
+        let (tx, rx) = channel::<Priority>();
+        std::thread::spawn(move || {
+            let mut backend = Backend::open();
+            for priority in rx {
+                let sound = SOUND.load(Ordering::Relaxed);
+                let vibrate = VIBRATE.load(Ordering::Relaxed);
+                if !sound && !vibrate {
+                    continue;
+                }
+                let pattern: Vec<Tone> = priority
+                    .pattern()
+                    .iter()
+                    .map(|t| Tone {
+                        freq: if sound { t.freq } else { 0 },
+                        vibrate: t.vibrate && vibrate,
+                        ..*t
+                    })
+                    .collect();
+                backend.play(&pattern);
+            }
+        });
+        tx
+    });
+    let _ = queue.send(priority);
+}
@@ -0,0 +1,103 @@
+//! Input recording and replay, `OPENPAGER_RECORD=<file>` logs every action
+//! with its time and `OPENPAGER_REPLAY=<file>` feeds such a log back in
+//! place of the keys
+//!
+//! Each line is the milliseconds since startup and the action's name,
+//! e.g. `1520 select`
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::platform::Action;
+
+pub struct Recorder {
+    file: File,
+    start: Instant,
+}
+
+impl Recorder {
+    /// Recorder for `OPENPAGER_RECORD` if it is set
+    pub fn from_env(start: Instant) -> Option<Self> {
+        let path = std::env::var_os("OPENPAGER_RECORD")?;
+        match Self::create(Path::new(&path), start) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                eprintln!("Error opening input log {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    fn create(path: &Path, start: Instant) -> io::Result<Self> {
+        let mut file = File::create(path)?;
+        writeln!(file, "# openpager input log")?;
+        Ok(Self { file, start })
+    }
+
+    /// Written straight through so the log survives a crash
+    pub fn record(&mut self, action: Action, at: Instant) {
+        let ms = at.duration_since(self.start).as_millis();
+        if let Err(e) = writeln!(self.file, "{} {}", ms, action.name()) {
+            eprintln!("Error writing input log: {}", e);
+        }
+    }
+}
+
+pub struct Replay {
+    actions: VecDeque<(Duration, Action)>,
+    start: Instant,
+}
+
+impl Replay {
+    /// Replay of `OPENPAGER_REPLAY` if it is set, timed from `start`
+    pub fn from_env(start: Instant) -> Option<Self> {
+        let path = std::env::var_os("OPENPAGER_REPLAY")?;
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Some(Self::parse(&text, start)),
+            Err(e) => {
+                eprintln!("Error reading input log {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    fn parse(text: &str, start: Instant) -> Self {
+        let mut actions = VecDeque::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = line
+                .split_once(' ')
+                .and_then(|(ms, name)| Some((ms.parse().ok()?, Action::from_name(name.trim())?)));
+            match parsed {
+                Some((ms, action)) => actions.push_back((Duration::from_millis(ms), action)),
+                None => eprintln!("Skipping input log line {}: {}", n + 1, line),
+            }
+        }
+        Self { actions, start }
+    }
+
+    /// Next recorded action once its time has come
+    pub fn poll(&mut self, now: Instant) -> Option<Action> {
+        let (at, action) = *self.actions.front()?;
+        if now < self.start + at {
+            return None;
+        }
+        self.actions.pop_front();
+        Some(action)
+    }
+
+    /// When the next action is due, `None` once the log is used up
+    pub fn next_due(&self) -> Option<Instant> {
+        self.actions.front().map(|(at, _)| self.start + *at)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
@@ -0,0 +1,385 @@
+//! Minimal RFB (VNC) server mirroring the screen and taking keys, enabled
+//! by `OPENPAGER_VNC=<address>`, a bare port binds to localhost
+//!
+//! Speaks protocol 3.3 to 3.8 without authentication and only the raw
+//! encoding, which is plenty for a 480x222 screen over USB networking.
+//! Arrows, Enter, Escape/Backspace, `p` and F12 map to the pager's keys.
+//!
+//! With no authentication anyone who reaches the port can read messages
+//! and drive the device, so addresses other than loopback are refused
+//! unless `OPENPAGER_VNC_INSECURE=1` says the network is trusted.
+
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::platform::{self, Action, RenderBuffer};
+use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+
+const DEFAULT_HOST: &str = "127.0.0.1";
+const NAME: &str = "OpenPager";
+
+/// Longest a client waiting for a change sleeps before checking again
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+struct Frame {
+    serial: u64,
+    width: u16,
+    height: u16,
+    pixels: Vec<u32>,
+}
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static FRAME: Mutex<Frame> = Mutex::new(Frame {
+    serial: 0,
+    width: 0,
+    height: 0,
+    pixels: Vec::new(),
+});
+static FRAME_CHANGED: Condvar = Condvar::new();
+
+/// Hands a drawn frame to connected clients, cheap while the server is off
+pub fn publish(render: &RenderBuffer) {
+    if !RUNNING.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut frame = FRAME.lock().unwrap();
+    frame.serial += 1;
+    frame.width = render.width as u16;
+    frame.height = render.height as u16;
+    frame.pixels.clone_from(&render.pixels);
+    FRAME_CHANGED.notify_all();
+}
+
+/// Starts the server when `OPENPAGER_VNC` is set, keys from clients arrive
+/// on the returned channel and wake the main loop
+pub fn start() -> Receiver<Action> {
+    let (tx, rx) = mpsc::channel();
+    let Some(address) = std::env::var("OPENPAGER_VNC").ok() else {
+        return rx;
+    };
+    let address = if address.contains(':') {
+        address
+    } else {
+        format!("{}:{}", DEFAULT_HOST, address)
+    };
+    match TcpListener::bind(&address) {
+        Ok(listener) => {
+            let loopback = listener.local_addr().is_ok_and(|a| a.ip().is_loopback());
+            if !loopback {
+                if std::env::var("OPENPAGER_VNC_INSECURE").as_deref() != Ok("1") {
+                    eprintln!(
+                        "Refusing to serve VNC without a password on {}, \
+                         set OPENPAGER_VNC_INSECURE=1 if that network is trusted",
+                        address
+                    );
+                    return rx;
+                }
+                eprintln!(
+                    "WARNING: VNC on {} has no password, anyone who can reach \
+                     it can read messages and press keys",
+                    address
+                );
+            }
+            let mut frame = FRAME.lock().unwrap();
+            frame.width = DISPLAY_WIDTH as u16;
+            frame.height = DISPLAY_HEIGHT as u16;
+            drop(frame);
+            RUNNING.store(true, Ordering::Relaxed);
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let tx = tx.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = serve(stream, tx) {
+                            eprintln!("VNC client error: {}", e);
+                        }
+                    });
+                }
+            });
+        }
+        Err(e) => eprintln!("Error starting VNC server on {}: {}", address, e),
+    }
+    rx
+}
+
+/// True-colour pixel layout a client asked for
+#[derive(Clone, Copy)]
+struct PixelFormat {
+    bits: u8,
+    big_endian: bool,
+    max: [u16; 3],
+    shift: [u8; 3],
+}
+
+impl PixelFormat {
+    /// What the server offers: 32-bit little-endian xRGB
+    const DEFAULT: Self = Self {
+        bits: 32,
+        big_endian: false,
+        max: [255; 3],
+        shift: [16, 8, 0],
+    };
+
+    fn to_bytes(self) -> [u8; 16] {
+        let mut out = [0u8; 16];
+        out[0] = self.bits;
+        out[1] = if self.bits == 32 { 24 } else { self.bits };
+        out[2] = self.big_endian as u8;
+        out[3] = 1;
+        for i in 0..3 {
+            out[4 + i * 2..6 + i * 2].copy_from_slice(&self.max[i].to_be_bytes());
+            out[10 + i] = self.shift[i];
+        }
+        out
+    }
+
+    fn from_bytes(b: &[u8; 16]) -> io::Result<Self> {
+        if b[3] == 0 {
+            return Err(io::Error::other(
+                "colour-map pixel formats are not supported",
+            ));
+        }
+        if !matches!(b[0], 8 | 16 | 32) {
+            return Err(io::Error::other(format!(
+                "unsupported {} bits per pixel",
+                b[0]
+            )));
+        }
+        let max = |i: usize| u16::from_be_bytes([b[4 + i * 2], b[5 + i * 2]]);
+        let format = Self {
+            bits: b[0],
+            big_endian: b[2] != 0,
+            max: [max(0), max(1), max(2)],
+            shift: [b[10], b[11], b[12]],
+        };
+        // Every channel has to fit in the pixel, or `write` shifts past it
+        for (max, shift) in format.max.iter().zip(format.shift) {
+            let width = (u16::BITS - max.leading_zeros()) as u8;
+            if *max == 0 || shift >= format.bits || shift + width > format.bits {
+                return Err(io::Error::other(format!(
+                    "channel with maximum {} at shift {} does not fit {} bits",
+                    max, shift, format.bits
+                )));
+            }
+        }
+        Ok(format)
+    }
+
+    /// Appends one 0xRRGGBB pixel in this format
+    fn write(&self, out: &mut Vec<u8>, rgb: u32) {
+        let channels = [(rgb >> 16) & 0xFF, (rgb >> 8) & 0xFF, rgb & 0xFF];
+        let value = (0..3).fold(0u32, |value, i| {
+            value | (channels[i] * self.max[i] as u32 / 255) << self.shift[i]
+        });
+        let bytes = if self.big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        };
+        let n = self.bits as usize / 8;
+        if self.big_endian {
+            out.extend_from_slice(&bytes[4 - n..]);
+        } else {
+            out.extend_from_slice(&bytes[..n]);
+        }
+    }
+}
+
+/// What the reader half tells the writer half of a connection
+struct Client {
+    format: PixelFormat,
+    /// Pending update request, `true` when only changes are wanted
+    request: Option<bool>,
+    closed: bool,
+}
+
+fn serve(stream: TcpStream, tx: Sender<Action>) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream.try_clone()?);
+
+    // Version, 3.3 clients get the older security handshake
+    writer.write_all(b"RFB 003.008\n")?;
+    writer.flush()?;
+    let mut version = [0u8; 12];
+    reader.read_exact(&mut version)?;
+    let minor = std::str::from_utf8(&version[8..11])
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(3);
+    if minor >= 7 {
+        // One security type, None
+        writer.write_all(&[1, 1])?;
+        writer.flush()?;
+        let mut chosen = [0u8; 1];
+        reader.read_exact(&mut chosen)?;
+        if minor >= 8 {
+            writer.write_all(&0u32.to_be_bytes())?;
+        }
+    } else {
+        writer.write_all(&1u32.to_be_bytes())?;
+    }
+    writer.flush()?;
+
+    // ClientInit carries only the shared flag
+    let mut shared = [0u8; 1];
+    reader.read_exact(&mut shared)?;
+
+    let (width, height) = {
+        let frame = FRAME.lock().unwrap();
+        (frame.width, frame.height)
+    };
+    writer.write_all(&width.to_be_bytes())?;
+    writer.write_all(&height.to_be_bytes())?;
+    writer.write_all(&PixelFormat::DEFAULT.to_bytes())?;
+    writer.write_all(&(NAME.len() as u32).to_be_bytes())?;
+    writer.write_all(NAME.as_bytes())?;
+    writer.flush()?;
+
+    let client = Arc::new(Mutex::new(Client {
+        format: PixelFormat::DEFAULT,
+        request: None,
+        closed: false,
+    }));
+    let updates = {
+        let client = client.clone();
+        thread::spawn(move || send_updates(writer, client))
+    };
+    let result = read_messages(&mut reader, &client, &tx);
+    client.lock().unwrap().closed = true;
+    let _ = stream.shutdown(std::net::Shutdown::Both);
+    let _ = updates.join();
+    result
+}
+
+fn read_messages(
+    reader: &mut impl Read,
+    client: &Mutex<Client>,
+    tx: &Sender<Action>,
+) -> io::Result<()> {
+    loop {
+        let mut kind = [0u8; 1];
+        if reader.read(&mut kind)? == 0 {
+            return Ok(());
+        }
+        match kind[0] {
+            // SetPixelFormat
+            0 => {
+                let mut body = [0u8; 19];
+                reader.read_exact(&mut body)?;
+                let format = PixelFormat::from_bytes(body[3..].try_into().unwrap())?;
+                client.lock().unwrap().format = format;
+            }
+            // SetEncodings, only raw is ever sent
+            2 => {
+                let mut body = [0u8; 3];
+                reader.read_exact(&mut body)?;
+                let count = u16::from_be_bytes([body[1], body[2]]) as usize;
+                io::copy(&mut reader.take(count as u64 * 4), &mut io::sink())?;
+            }
+            // FramebufferUpdateRequest, always answered with the whole screen
+            3 => {
+                let mut body = [0u8; 9];
+                reader.read_exact(&mut body)?;
+                let incremental = body[0] != 0;
+                let mut client = client.lock().unwrap();
+                client.request = Some(client.request.unwrap_or(true) && incremental);
+                FRAME_CHANGED.notify_all();
+            }
+            // KeyEvent
+            4 => {
+                let mut body = [0u8; 7];
+                reader.read_exact(&mut body)?;
+                let down = body[0] != 0;
+                let keysym = u32::from_be_bytes([body[3], body[4], body[5], body[6]]);
+                if let Some(action) = key_action(keysym)
+                    && down
+                {
+                    if tx.send(action).is_err() {
+                        return Ok(());
+                    }
+                    platform::wake();
+                }
+            }
+            // PointerEvent
+            5 => {
+                let mut body = [0u8; 5];
+                reader.read_exact(&mut body)?;
+            }
+            // ClientCutText
+            6 => {
+                let mut body = [0u8; 7];
+                reader.read_exact(&mut body)?;
+                let len = u32::from_be_bytes([body[3], body[4], body[5], body[6]]);
+                io::copy(&mut reader.take(len as u64), &mut io::sink())?;
+            }
+            other => {
+                return Err(io::Error::other(format!("unknown message type {}", other)));
+            }
+        }
+    }
+}
+
+/// X keysyms the pager's buttons are mapped to
+fn key_action(keysym: u32) -> Option<Action> {
+    match keysym {
+        0xFF52 => Some(Action::Up),
+        0xFF54 => Some(Action::Down),
+        0xFF0D | 0xFF8D => Some(Action::Select),
+        0xFF1B | 0xFF08 => Some(Action::Back),
+        0x70 | 0x50 => Some(Action::Power),
+        0xFFC9 => Some(Action::Screenshot),
+        _ => None,
+    }
+}
+
+/// Answers update requests, holding incremental ones until the screen
+/// changes
+fn send_updates(mut writer: impl Write, client: Arc<Mutex<Client>>) {
+    let mut sent = 0;
+    loop {
+        let (format, frame) = {
+            let mut frame = FRAME.lock().unwrap();
+            loop {
+                let client = client.lock().unwrap();
+                if client.closed {
+                    return;
+                }
+                // Nothing to send before the first frame is drawn
+                match client.request {
+                    Some(false) if frame.serial != 0 => break,
+                    Some(true) if frame.serial != sent => break,
+                    _ => {}
+                }
+                drop(client);
+                frame = FRAME_CHANGED.wait_timeout(frame, POLL_INTERVAL).unwrap().0;
+            }
+            let mut client = client.lock().unwrap();
+            client.request = None;
+            sent = frame.serial;
+            (
+                client.format,
+                (frame.width, frame.height, frame.pixels.clone()),
+            )
+        };
+
+        let (width, height, pixels) = frame;
+        let mut out = Vec::with_capacity(16 + pixels.len() * format.bits as usize / 8);
+        // FramebufferUpdate with one raw rectangle covering the screen
+        out.extend_from_slice(&[0, 0, 0, 1]);
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        out.extend_from_slice(&width.to_be_bytes());
+        out.extend_from_slice(&height.to_be_bytes());
+        out.extend_from_slice(&0i32.to_be_bytes());
+        for &pixel in pixels.iter() {
+            format.write(&mut out, pixel);
+        }
+        if writer.write_all(&out).and_then(|_| writer.flush()).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Screenshots of the render buffer, saved as PNG in `screenshots/` under
+//! the config directory
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::config;
+use crate::platform::{RenderBuffer, clock};
+
+/// Writes the frame in its logical orientation, returns the file's path
+pub fn save(render: &RenderBuffer) -> io::Result<PathBuf> {
+    let dir = config::path("screenshots");
+    fs::create_dir_all(&dir)?;
+    let stamp = clock::timestamp();
+    let mut path = dir.join(format!("screenshot-{}.png", stamp));
+    // Several in the same second get a counter
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!("screenshot-{}-{}.png", stamp, n));
+    }
+    fs::write(&path, encode(render))?;
+    Ok(path)
+}
+
+/// 8-bit RGB PNG, deflate with stored blocks since the files are small
+/// and it avoids a compression dependency
+pub fn encode(render: &RenderBuffer) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(((render.width * 3 + 1) * render.height) as usize);
+    for row in render.pixels.chunks(render.width as usize) {
+        // Filter type: none
+        raw.push(0);
+        for pixel in row {
+            raw.extend_from_slice(&pixel.to_be_bytes()[1..]);
+        }
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&render.width.to_be_bytes());
+    header.extend_from_slice(&render.height.to_be_bytes());
+    // Bit depth 8, color type RGB, default compression, filter and no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut png, b"IHDR", &header);
+    chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xFFFF;
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK * 5 + 11);
+    // Deflate with a 32K window, no preset dictionary
+    out.extend_from_slice(&[0x78, 0x01]);
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // Sums stay below 2^32 for this many bytes between reductions
+    for block in data.chunks(5552) {
+        for &byte in block {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
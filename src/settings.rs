@@ -0,0 +1,270 @@
+//! Typed settings registry, persisted to `settings.conf`
+
+use std::sync::Mutex;
+
+use crate::config;
+use crate::platform;
+use crate::ui::theme;
+
+pub enum Kind {
+    Bool,
+    Range { min: i32, max: i32, step: i32 },
+    Choice(&'static [&'static str]),
+    Text { max_len: usize },
+    /// Button sequence, stored as `U`, `D` and `S` for Up, Down and Select
+    Code { max_len: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Bool(bool),
+    Int(i32),
+    Choice(usize),
+    Text(String),
+}
+
+pub struct Setting {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub kind: Kind,
+    default: &'static str,
+}
+
+const FILE: &str = "settings.conf";
+
+pub static SETTINGS: &[Setting] = &[
+    Setting {
+        key: "display.name",
+        label: "Device name",
+        kind: Kind::Text { max_len: 16 },
+        default: "OpenPager",
+    },
+    Setting {
+        key: "display.theme",
+        label: "Theme",
+        kind: Kind::Choice(&["dark", "light", "high-contrast", "custom"]),
+        default: "dark",
+    },
+    Setting {
+        key: "display.brightness",
+        label: "Brightness",
+        kind: Kind::Range {
+            min: 10,
+            max: 100,
+            step: 10,
+        },
+        default: "80",
+    },
+    Setting {
+        key: "display.clock_after",
+        label: "Clock face after (min)",
+        kind: Kind::Range {
+            min: 0,
+            max: 30,
+            step: 1,
+        },
+        default: "5",
+    },
+    Setting {
+        key: "sound.enabled",
+        label: "Sound",
+        kind: Kind::Bool,
+        default: "true",
+    },
+    Setting {
+        key: "sound.vibrate",
+        label: "Vibration",
+        kind: Kind::Bool,
+        default: "true",
+    },
+    Setting {
+        key: "dnd.mode",
+        label: "Mode",
+        kind: Kind::Choice(&["off", "on", "scheduled"]),
+        default: "off",
+    },
+    Setting {
+        key: "dnd.start",
+        label: "Quiet from",
+        kind: Kind::Range {
+            min: 0,
+            max: 23,
+            step: 1,
+        },
+        default: "22",
+    },
+    Setting {
+        key: "dnd.end",
+        label: "Quiet until",
+        kind: Kind::Range {
+            min: 0,
+            max: 23,
+            step: 1,
+        },
+        default: "7",
+    },
+    Setting {
+        key: "network.hostname",
+        label: "Hostname",
+        kind: Kind::Text { max_len: 24 },
+        // Empty leaves the name from DHCP or the image alone
+        default: "",
+    },
+    Setting {
+        key: "lock.code",
+        label: "Unlock code",
+        kind: Kind::Code { max_len: 8 },
+        default: "",
+    },
+    Setting {
+        key: "lock.timeout",
+        label: "Lock after (min)",
+        kind: Kind::Range {
+            min: 0,
+            max: 60,
+            step: 5,
+        },
+        default: "0",
+    },
+];
+
+impl Setting {
+    fn parse(&self, raw: &str) -> Option<Value> {
+        match self.kind {
+            Kind::Bool => match raw {
+                "true" | "on" | "1" => Some(Value::Bool(true)),
+                "false" | "off" | "0" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            Kind::Range { min, max, .. } => {
+                raw.parse().ok().map(|v: i32| Value::Int(v.clamp(min, max)))
+            }
+            Kind::Choice(options) => options.iter().position(|o| *o == raw).map(Value::Choice),
+            Kind::Text { max_len } => Some(Value::Text(raw.chars().take(max_len).collect())),
+            Kind::Code { max_len } => Some(Value::Text(
+                raw.chars().filter(|c| "UDS".contains(*c)).take(max_len).collect(),
+            )),
+        }
+    }
+
+    fn format(&self, value: &Value) -> String {
+        match (value, &self.kind) {
+            (Value::Bool(b), _) => b.to_string(),
+            (Value::Int(i), _) => i.to_string(),
+            (Value::Choice(i), Kind::Choice(options)) => options[*i].to_string(),
+            (Value::Choice(i), _) => i.to_string(),
+            (Value::Text(s), _) => s.clone(),
+        }
+    }
+
+    fn default_value(&self) -> Value {
+        self.parse(self.default).expect("invalid setting default")
+    }
+}
+
+static VALUES: Mutex<Vec<Value>> = Mutex::new(Vec::new());
+
+fn index(key: &str) -> usize {
+    SETTINGS
+        .iter()
+        .position(|s| s.key == key)
+        .unwrap_or_else(|| panic!("unknown setting {}", key))
+}
+
+/// Reads the settings file, falling back to defaults, and applies everything
+pub fn load() {
+    let mut values: Vec<Value> = SETTINGS.iter().map(Setting::default_value).collect();
+    if let Ok(text) = std::fs::read_to_string(config::path(FILE)) {
+        for (key, raw) in config::parse(&text) {
+            if let Some(i) = SETTINGS.iter().position(|s| s.key == key)
+                && let Some(value) = SETTINGS[i].parse(&raw)
+            {
+                values[i] = value;
+            }
+        }
+    }
+    *VALUES.lock().unwrap() = values;
+    for setting in SETTINGS {
+        apply(setting.key);
+    }
+}
+
+pub fn get(key: &str) -> Value {
+    let i = index(key);
+    let values = VALUES.lock().unwrap();
+    values
+        .get(i)
+        .cloned()
+        .unwrap_or_else(|| SETTINGS[i].default_value())
+}
+
+pub fn get_bool(key: &str) -> bool {
+    matches!(get(key), Value::Bool(true))
+}
+
+pub fn get_int(key: &str) -> i32 {
+    match get(key) {
+        Value::Int(i) => i,
+        _ => 0,
+    }
+}
+
+pub fn get_choice(key: &str) -> usize {
+    match get(key) {
+        Value::Choice(i) => i,
+        _ => 0,
+    }
+}
+
+pub fn get_text(key: &str) -> String {
+    match get(key) {
+        Value::Text(s) => s,
+        _ => String::new(),
+    }
+}
+
+/// Changes a setting, applies it right away and saves the file
+pub fn set(key: &str, value: Value) {
+    let i = index(key);
+    {
+        let mut values = VALUES.lock().unwrap();
+        if values.len() != SETTINGS.len() {
+            *values = SETTINGS.iter().map(Setting::default_value).collect();
+        }
+        if values[i] == value {
+            return;
+        }
+        values[i] = value;
+    }
+    apply(key);
+    if let Err(e) = save() {
+        eprintln!("Error saving settings: {}", e);
+        crate::ui::toast::show("Could not save settings");
+    }
+}
+
+fn save() -> std::io::Result<()> {
+    let values = VALUES.lock().unwrap().clone();
+    let mut out = String::from("# openpager settings\n");
+    for (setting, value) in SETTINGS.iter().zip(&values) {
+        out.push_str(&format!("{} = {}\n", setting.key, setting.format(value)));
+    }
+    config::write_atomic(&config::path(FILE), &out)
+}
+
+/// Pushes settings that live outside this registry to their subsystem
+fn apply(key: &str) {
+    match key {
+        "display.theme" => {
+            if let Kind::Choice(options) = SETTINGS[index(key)].kind {
+                theme::select(options[get_choice(key)]);
+            }
+        }
+        "display.brightness" => platform::set_brightness(get_int(key) as u8),
+        "sound.enabled" | "sound.vibrate" => {
+            platform::set_alert_output(get_bool("sound.enabled"), get_bool("sound.vibrate"))
+        }
+        "network.hostname" => platform::set_hostname(&get_text(key)),
+        _ => {}
+    }
+}
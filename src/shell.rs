@@ -0,0 +1,215 @@
+//! Shell commands run from menu items, through `sh -c`
+
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::platform;
+use crate::ui::toast;
+
+/// Output kept from a job, older output is dropped past this
+const MAX_OUTPUT: usize = 64 * 1024;
+
+/// How often a job's supervisor checks for exit, timeout and cancel
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+fn command(line: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(line);
+    // Own process group, so a kill takes the shell's children with it
+    command.process_group(0);
+    command
+}
+
+/// Runs `line` in the background and toasts how it went
+pub fn run_detached(label: &str, line: &str) {
+    let label = label.to_string();
+    let result = command(line).spawn();
+    match result {
+        Ok(mut child) => {
+            thread::spawn(move || match child.wait() {
+                Ok(status) if status.success() => toast::show(format!("{}: done", label)),
+                Ok(status) => toast::show(format!("{}: {}", label, End::from(status))),
+                Err(e) => toast::show(format!("{}: {}", label, e)),
+            });
+        }
+        Err(e) => {
+            eprintln!("Error running {:?}: {}", line, e);
+            toast::show(format!("{}: {}", label, e));
+        }
+    }
+}
+
+/// How a job finished
+#[derive(Clone, Debug)]
+pub enum End {
+    Exited(i32),
+    Signalled(i32),
+    TimedOut(Duration),
+    Cancelled,
+    Failed(String),
+}
+
+impl From<std::process::ExitStatus> for End {
+    fn from(status: std::process::ExitStatus) -> Self {
+        use std::os::unix::process::ExitStatusExt;
+        match (status.code(), status.signal()) {
+            (Some(code), _) => End::Exited(code),
+            (None, Some(signal)) => End::Signalled(signal),
+            (None, None) => End::Failed("unknown exit status".into()),
+        }
+    }
+}
+
+impl std::fmt::Display for End {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            End::Exited(code) => write!(f, "exit {}", code),
+            End::Signalled(signal) => write!(f, "killed by signal {}", signal),
+            End::TimedOut(after) => write!(f, "timed out after {:?}", after),
+            End::Cancelled => write!(f, "cancelled"),
+            End::Failed(e) => write!(f, "could not run: {}", e),
+        }
+    }
+}
+
+#[derive(Default)]
+struct JobState {
+    /// stdout and stderr interleaved as they arrive
+    output: Vec<u8>,
+    /// Bumped on every change, to tell when to look again
+    serial: u64,
+    end: Option<End>,
+}
+
+/// Command running in the background with its output captured, for views
+/// that show it as it arrives
+pub struct Job {
+    state: Arc<Mutex<JobState>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Job {
+    /// Starts `line`, killing it once `timeout` passes
+    pub fn start(line: &str, timeout: Option<Duration>) -> Self {
+        let job = Self {
+            state: Arc::default(),
+            cancel: Arc::default(),
+        };
+        let spawned = command(line)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        match spawned {
+            Ok(child) => {
+                let state = job.state.clone();
+                let cancel = job.cancel.clone();
+                thread::spawn(move || supervise(child, state, cancel, timeout));
+            }
+            Err(e) => {
+                eprintln!("Error running {:?}: {}", line, e);
+                job.state.lock().unwrap().end = Some(End::Failed(e.to_string()));
+            }
+        }
+        job
+    }
+
+    pub fn serial(&self) -> u64 {
+        self.state.lock().unwrap().serial
+    }
+
+    /// Output so far and how the job ended, if it has
+    pub fn snapshot(&self) -> (String, Option<End>) {
+        let state = self.state.lock().unwrap();
+        (
+            String::from_utf8_lossy(&state.output).into_owned(),
+            state.end.clone(),
+        )
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state.lock().unwrap().end.is_none()
+    }
+
+    /// Kills the command if it is still running
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+fn supervise(
+    mut child: Child,
+    state: Arc<Mutex<JobState>>,
+    cancel: Arc<AtomicBool>,
+    timeout: Option<Duration>,
+) {
+    let readers = [
+        child.stdout.take().map(|out| capture(out, state.clone())),
+        child.stderr.take().map(|err| capture(err, state.clone())),
+    ];
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut killed = None;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Ok(status),
+            Ok(None) => {}
+            Err(e) => break Err(e),
+        }
+        if killed.is_none() {
+            if cancel.load(Ordering::Relaxed) {
+                killed = Some(End::Cancelled);
+            } else if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                killed = Some(End::TimedOut(timeout.unwrap()));
+            }
+            if killed.is_some() {
+                kill_group(&child);
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    // Anything the shell left in the background would hold the pipes open
+    kill_group(&child);
+    for reader in readers.into_iter().flatten() {
+        let _ = reader.join();
+    }
+    let end = match (killed, status) {
+        (Some(end), _) => end,
+        (None, Ok(status)) => End::from(status),
+        (None, Err(e)) => End::Failed(e.to_string()),
+    };
+    let mut state = state.lock().unwrap();
+    state.end = Some(end);
+    state.serial += 1;
+    drop(state);
+    platform::wake();
+}
+
+/// The whole group, the pipes only close once all of it is gone
+fn kill_group(child: &Child) {
+    unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+}
+
+fn capture(
+    mut pipe: impl Read + Send + 'static,
+    state: Arc<Mutex<JobState>>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        while let Ok(n @ 1..) = pipe.read(&mut buf) {
+            let mut state = state.lock().unwrap();
+            state.output.extend_from_slice(&buf[..n]);
+            if state.output.len() > MAX_OUTPUT {
+                let excess = state.output.len() - MAX_OUTPUT;
+                state.output.drain(..excess);
+            }
+            state.serial += 1;
+            drop(state);
+            platform::wake();
+        }
+    })
+}
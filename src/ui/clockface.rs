@@ -0,0 +1,105 @@
+//! Always-on clock shown after a while without input, dimmed and drifting
+//! a little every minute so nothing burns into the panel
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use embedded_graphics::{pixelcolor::Rgb888, prelude::*, text::Text};
+
+use super::font::{self, FontStyle};
+use super::segments;
+use super::{StatusBar, Theme, View, ViewResult};
+use crate::inbox;
+use crate::platform::{self, Action, RenderBuffer, read_battery};
+use crate::settings;
+
+const CLOCK_HEIGHT: u32 = 60;
+/// Summary line below the clock
+const SUMMARY_GAP: i32 = 28;
+const DIM_BRIGHTNESS: u8 = 20;
+
+/// Pixels moved per minute
+const DRIFT_X: i32 = 7;
+const DRIFT_Y: i32 = 3;
+
+static SHOWING: AtomicBool = AtomicBool::new(false);
+
+/// Whether the clock face is on the view stack
+pub fn is_showing() -> bool {
+    SHOWING.load(Ordering::Relaxed)
+}
+
+/// When the clock face comes up after `last_input`, `None` when that is
+/// turned off or it already is up
+pub fn deadline(last_input: Instant) -> Option<Instant> {
+    let minutes = settings::get_int("display.clock_after");
+    if minutes <= 0 || is_showing() {
+        return None;
+    }
+    Some(last_input + Duration::from_secs(minutes as u64 * 60))
+}
+
+/// Position `distance` along a path going back and forth over `0..=range`
+fn bounce(distance: i32, range: i32) -> i32 {
+    let range = range.max(1);
+    let t = distance.rem_euclid(2 * range);
+    if t > range { 2 * range - t } else { t }
+}
+
+pub struct ClockFace;
+
+impl View for ClockFace {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        display.clear(Rgb888::BLACK).unwrap();
+        let bounds = display.bounding_box();
+
+        let (hours, minutes) = StatusBar::read_time();
+        let time = format!("{:02}:{:02}", hours, minutes);
+        let (battery, _) = read_battery();
+        let unread = inbox::pending();
+        let summary = format!("{}%  ·  {} unread", battery, unread);
+        let style = FontStyle::new(font::small(), theme.muted);
+
+        let clock_width = segments::measure(&time, CLOCK_HEIGHT);
+        let summary_width = font::small().measure(&summary);
+        let width = clock_width.max(summary_width);
+        let height = CLOCK_HEIGHT as i32 + SUMMARY_GAP;
+
+        // Drifts a few pixels a minute, bouncing off the screen edges
+        let step = hours as i32 * 60 + minutes as i32;
+        let x = bounce(step * DRIFT_X, (bounds.size.width - width) as i32);
+        let y = bounce(step * DRIFT_Y, bounds.size.height as i32 - height);
+
+        let clock_x = x + (width - clock_width) as i32 / 2;
+        segments::draw(
+            display,
+            &time,
+            Point::new(clock_x, y),
+            CLOCK_HEIGHT,
+            theme.muted,
+        );
+        let summary_x = x + (width - summary_width) as i32 / 2;
+        Text::new(&summary, Point::new(summary_x, y + height - 4), style)
+            .draw(display)
+            .unwrap();
+    }
+
+    fn handle(&mut self, _action: Action) -> ViewResult {
+        ViewResult::Pop
+    }
+
+    fn title(&self) -> &str {
+        "Clock"
+    }
+
+    fn on_enter(&mut self) {
+        SHOWING.store(true, Ordering::Relaxed);
+        let brightness = settings::get_int("display.brightness") as u8;
+        platform::set_brightness(brightness.min(DIM_BRIGHTNESS));
+    }
+
+    fn on_exit(&mut self) {
+        SHOWING.store(false, Ordering::Relaxed);
+        platform::set_brightness(settings::get_int("display.brightness") as u8);
+    }
+}
@@ -1,14 +0,0 @@
-use embedded_graphics::pixelcolor::Rgb888;
-
-pub const BACKGROUND: Rgb888 = Rgb888::new(0x28, 0x2A, 0x36);
-pub const CURRENT_LINE: Rgb888 = Rgb888::new(0x62, 0x72, 0xA4);
-pub const SELECTION: Rgb888 = Rgb888::new(0x44, 0x47, 0x5A);
-pub const FOREGROUND: Rgb888 = Rgb888::new(0xF8, 0xF8, 0xF2);
-pub const COMMENT: Rgb888 = Rgb888::new(0x62, 0x72, 0xA4);
-pub const RED: Rgb888 = Rgb888::new(0xFF, 0x55, 0x55);
-pub const ORANGE: Rgb888 = Rgb888::new(0xFF, 0xB8, 0x6C);
-pub const YELLOW: Rgb888 = Rgb888::new(0xF1, 0xFA, 0x8C);
-pub const GREEN: Rgb888 = Rgb888::new(0x50, 0xFA, 0x7B);
-pub const CYAN: Rgb888 = Rgb888::new(0x8B, 0xE9, 0xFD);
-pub const PURPLE: Rgb888 = Rgb888::new(0xBD, 0x93, 0xF9);
-pub const PINK: Rgb888 = Rgb888::new(0xFF, 0x79, 0xC6);
@@ -0,0 +1,113 @@
+use std::time::{Duration, Instant};
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::{Circle, PrimitiveStyle},
+};
+
+use super::font::{self, FontStyle};
+use super::layout::{self, Align};
+use super::text::TextView;
+use super::{STATUSBAR_HEIGHT, Theme, View, ViewResult};
+use crate::platform::{Action, RenderBuffer};
+use crate::shell::Job;
+
+/// Spinner dots and how fast the lit one goes round
+const SPINNER_DOTS: usize = 8;
+const SPINNER_RADIUS: f32 = 6.0;
+const SPINNER_STEP: Duration = Duration::from_millis(100);
+
+/// Runs a shell command, showing its output as it arrives with a spinner
+/// in the header and how it exited once done. Back kills it.
+pub struct CommandView {
+    body: TextView,
+    job: Job,
+    serial: u64,
+    started: Instant,
+    now: Instant,
+}
+
+impl CommandView {
+    pub fn new(title: impl Into<String>, line: &str, timeout: Option<Duration>) -> Self {
+        let now = Instant::now();
+        Self {
+            body: TextView::new(title, "").following(),
+            job: Job::start(line, timeout),
+            serial: 0,
+            started: now,
+            now,
+        }
+    }
+
+    fn draw_spinner(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let width = display.bounding_box().size.width as i32;
+        let center = Point::new(width - 18, STATUSBAR_HEIGHT + 10);
+        let elapsed = self.now.duration_since(self.started);
+        let lit = (elapsed.as_millis() / SPINNER_STEP.as_millis()) as usize % SPINNER_DOTS;
+        for i in 0..SPINNER_DOTS {
+            let angle = i as f32 * std::f32::consts::TAU / SPINNER_DOTS as f32;
+            let dot = center
+                + Point::new(
+                    (angle.sin() * SPINNER_RADIUS) as i32,
+                    (-angle.cos() * SPINNER_RADIUS) as i32,
+                );
+            let color = if i == lit {
+                theme.foreground
+            } else {
+                theme.muted
+            };
+            Circle::with_center(dot, 3)
+                .into_styled(PrimitiveStyle::with_fill(color))
+                .draw(display)
+                .unwrap();
+        }
+
+        layout::draw_line(
+            display,
+            &format!("{}s", elapsed.as_secs()),
+            FontStyle::new(font::small(), theme.muted),
+            Point::new(width - 90, STATUSBAR_HEIGHT + 15),
+            60,
+            Align::Right,
+        );
+    }
+}
+
+impl View for CommandView {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        self.body.render(display, theme);
+        if self.job.is_running() {
+            self.draw_spinner(display, theme);
+        }
+    }
+
+    fn tick(&mut self, now: Instant) -> Option<Instant> {
+        self.now = now;
+        let serial = self.job.serial();
+        if serial != self.serial {
+            self.serial = serial;
+            let (mut text, end) = self.job.snapshot();
+            if let Some(end) = end {
+                if !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(&format!("[{}]", end));
+            }
+            self.body.set_text(&text);
+        }
+        self.job.is_running().then(|| now + SPINNER_STEP)
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        self.body.handle(action)
+    }
+
+    fn on_exit(&mut self) {
+        // Back while running, or the whole stack going away
+        self.job.cancel();
+    }
+
+    fn title(&self) -> &str {
+        self.body.title()
+    }
+}
@@ -0,0 +1,487 @@
+//! Modal dialogs drawn over the view beneath them: confirm, info, choice
+//! and progress
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::thread;
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+use super::font::{self, FontStyle};
+use super::layout::{self, Align};
+use super::{Reply, STATUSBAR_HEIGHT, Theme, View, ViewResult};
+use crate::platform::{self, Action, RenderBuffer};
+
+const MARGIN: i32 = 40;
+const BORDER: u32 = 2;
+const HEADER_HEIGHT: i32 = 32;
+const ROW_HEIGHT: i32 = 22;
+const BUTTON_WIDTH: u32 = 80;
+const BAR_HEIGHT: u32 = 10;
+/// Options shown at once by `Choice`, it scrolls past that
+const CHOICE_ROWS: usize = 5;
+
+/// Rows that fit between the header and the bottom of the screen
+fn max_rows(height: u32) -> usize {
+    ((height as i32 - STATUSBAR_HEIGHT - HEADER_HEIGHT - 16) / ROW_HEIGHT).max(1) as usize
+}
+
+/// Body of a drawn dialog, rows are laid out from `top`
+pub(super) struct Frame {
+    pub left: i32,
+    pub width: u32,
+    top: i32,
+}
+
+impl Frame {
+    /// Alphabetic baseline of body row `row`
+    pub fn baseline(&self, row: usize) -> i32 {
+        self.top + row as i32 * ROW_HEIGHT + 16
+    }
+}
+
+/// Draws a bordered, titled box centered below the status bar with room
+/// for `rows` body rows
+pub(super) fn draw_frame(
+    display: &mut RenderBuffer,
+    theme: &Theme,
+    title: &str,
+    rows: usize,
+) -> Frame {
+    let bounds = display.bounding_box();
+    let width = bounds.size.width - 2 * MARGIN as u32;
+    let height = (HEADER_HEIGHT + rows as i32 * ROW_HEIGHT + 8) as u32;
+    let area = bounds.size.height as i32 - STATUSBAR_HEIGHT;
+    let top = STATUSBAR_HEIGHT + ((area - height as i32) / 2).max(0);
+
+    let outer = Rectangle::new(Point::new(MARGIN, top), Size::new(width, height));
+    outer
+        .into_styled(PrimitiveStyle::with_fill(theme.separator))
+        .draw(display)
+        .unwrap();
+    outer
+        .offset(-(BORDER as i32))
+        .into_styled(PrimitiveStyle::with_fill(theme.background))
+        .draw(display)
+        .unwrap();
+
+    let inner_width = width - 20;
+    layout::draw_line(
+        display,
+        title,
+        FontStyle::new(font::regular(), theme.title),
+        Point::new(MARGIN + 10, top + 20),
+        inner_width,
+        Align::Left,
+    );
+    Rectangle::new(
+        Point::new(MARGIN + BORDER as i32, top + 26),
+        Size::new(width - 2 * BORDER, 1),
+    )
+    .into_styled(PrimitiveStyle::with_fill(theme.separator))
+    .draw(display)
+    .unwrap();
+
+    Frame {
+        left: MARGIN + 10,
+        width: inner_width,
+        top: top + HEADER_HEIGHT,
+    }
+}
+
+/// Draws `labels` as buttons right-aligned on body row `row`
+fn draw_buttons(
+    display: &mut RenderBuffer,
+    theme: &Theme,
+    frame: &Frame,
+    row: usize,
+    labels: &[&str],
+    selected: usize,
+) {
+    let y = frame.baseline(row);
+    let right = frame.left + frame.width as i32;
+    for (i, label) in labels.iter().enumerate() {
+        let x = right - ((labels.len() - i) as u32 * (BUTTON_WIDTH + 10)) as i32 + 10;
+        let color = if i == selected {
+            Rectangle::new(
+                Point::new(x, y - 15),
+                Size::new(BUTTON_WIDTH, ROW_HEIGHT as u32 - 2),
+            )
+            .into_styled(PrimitiveStyle::with_fill(theme.highlight))
+            .draw(display)
+            .unwrap();
+            theme.highlight_text
+        } else {
+            theme.foreground
+        };
+        layout::draw_line(
+            display,
+            label,
+            FontStyle::new(font::regular(), color),
+            Point::new(x, y),
+            BUTTON_WIDTH,
+            Align::Center,
+        );
+    }
+}
+
+/// Wraps `message` for the dialog body, keeping at most `rows` lines
+fn message_lines(message: &str, width: u32, rows: usize) -> Vec<String> {
+    let mut lines = layout::wrap(font::regular(), message, width);
+    if lines.len() > rows {
+        lines.truncate(rows);
+        let font = font::regular();
+        let last = lines.pop().unwrap_or_default();
+        // Mark the cut even when the last kept line fits
+        let marked = format!("{}{}", last, layout::ellipsis(font));
+        lines.push(layout::truncate(font, &marked, width).into_owned());
+    }
+    lines
+}
+
+pub(super) fn draw_message(
+    display: &mut RenderBuffer,
+    theme: &Theme,
+    frame: &Frame,
+    lines: &[String],
+) {
+    let style = FontStyle::new(font::regular(), theme.foreground);
+    for (row, line) in lines.iter().enumerate() {
+        layout::draw_line(
+            display,
+            line,
+            style,
+            Point::new(frame.left, frame.baseline(row)),
+            frame.width,
+            Align::Left,
+        );
+    }
+}
+
+pub(super) fn body_width(display: &RenderBuffer) -> u32 {
+    display.bounding_box().size.width - 2 * MARGIN as u32 - 20
+}
+
+/// Yes/no question, replies with a `bool`, Back answers no
+pub struct Confirm {
+    title: &'static str,
+    message: String,
+    yes: bool,
+}
+
+impl Confirm {
+    pub fn new(title: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            title,
+            message: message.into(),
+            yes: false,
+        }
+    }
+}
+
+impl View for Confirm {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let rows = max_rows(display.bounding_box().size.height) - 1;
+        let lines = message_lines(&self.message, body_width(display), rows);
+        let frame = draw_frame(display, theme, self.title, lines.len() + 1);
+        draw_message(display, theme, &frame, &lines);
+        draw_buttons(
+            display,
+            theme,
+            &frame,
+            lines.len(),
+            &["No", "Yes"],
+            self.yes as usize,
+        );
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        match action {
+            Action::Up | Action::Down => {
+                self.yes = !self.yes;
+                ViewResult::None
+            }
+            Action::Select => ViewResult::PopWith(Reply::new(self.yes)),
+            Action::Back => ViewResult::PopWith(Reply::new(false)),
+            Action::Power | Action::Screenshot => ViewResult::None,
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn title(&self) -> &str {
+        self.title
+    }
+
+    fn labels(&self) -> Vec<String> {
+        vec!["No".into(), "Yes".into()]
+    }
+
+    fn selected(&self) -> Option<usize> {
+        Some(self.yes as usize)
+    }
+}
+
+/// Message with a single OK button
+pub struct Info {
+    title: &'static str,
+    message: String,
+}
+
+impl Info {
+    pub fn new(title: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            title,
+            message: message.into(),
+        }
+    }
+}
+
+impl View for Info {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let rows = max_rows(display.bounding_box().size.height) - 1;
+        let lines = message_lines(&self.message, body_width(display), rows);
+        let frame = draw_frame(display, theme, self.title, lines.len() + 1);
+        draw_message(display, theme, &frame, &lines);
+        draw_buttons(display, theme, &frame, lines.len(), &["OK"], 0);
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        match action {
+            Action::Select | Action::Back => ViewResult::Pop,
+            Action::Up | Action::Down | Action::Power | Action::Screenshot => ViewResult::None,
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn title(&self) -> &str {
+        self.title
+    }
+}
+
+/// Single-choice list, replies with the chosen index as a `usize`, Back
+/// pops without a reply
+pub struct Choice {
+    title: &'static str,
+    options: Vec<String>,
+    selected: usize,
+    scroll: usize,
+}
+
+impl Choice {
+    pub fn new(title: &'static str, options: &[&str], selected: usize) -> Self {
+        let selected = selected.min(options.len().saturating_sub(1));
+        Self {
+            title,
+            options: options.iter().map(|option| option.to_string()).collect(),
+            selected,
+            scroll: (selected + 1).saturating_sub(CHOICE_ROWS),
+        }
+    }
+}
+
+impl View for Choice {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let visible = CHOICE_ROWS.min(self.options.len());
+        let frame = draw_frame(display, theme, self.title, visible);
+        let end = (self.scroll + visible).min(self.options.len());
+        for (row, i) in (self.scroll..end).enumerate() {
+            let y = frame.baseline(row);
+            let color = if i == self.selected {
+                Rectangle::new(
+                    Point::new(frame.left - 5, y - 15),
+                    Size::new(frame.width + 10, ROW_HEIGHT as u32 - 2),
+                )
+                .into_styled(PrimitiveStyle::with_fill(theme.highlight))
+                .draw(display)
+                .unwrap();
+                theme.highlight_text
+            } else {
+                theme.foreground
+            };
+            layout::draw_line(
+                display,
+                &self.options[i],
+                FontStyle::new(font::regular(), color),
+                Point::new(frame.left, y),
+                frame.width,
+                Align::Left,
+            );
+        }
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        match action {
+            Action::Up => {
+                if self.selected > 0 {
+                    self.selected -= 1;
+                    self.scroll = self.scroll.min(self.selected);
+                }
+            }
+            Action::Down => {
+                if self.selected + 1 < self.options.len() {
+                    self.selected += 1;
+                    if self.selected >= self.scroll + CHOICE_ROWS {
+                        self.scroll = self.selected + 1 - CHOICE_ROWS;
+                    }
+                }
+            }
+            Action::Select if !self.options.is_empty() => {
+                return ViewResult::PopWith(Reply::new(self.selected));
+            }
+            Action::Select | Action::Power | Action::Screenshot => {}
+            Action::Back => return ViewResult::Pop,
+        }
+        ViewResult::None
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn title(&self) -> &str {
+        self.title
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.options.clone()
+    }
+
+    fn selected(&self) -> Option<usize> {
+        Some(self.selected)
+    }
+}
+
+/// Shared between a `Progress` dialog and the task it is waiting on
+#[derive(Clone, Default)]
+pub struct ProgressHandle(Arc<ProgressState>);
+
+#[derive(Default)]
+struct ProgressState {
+    permille: AtomicU32,
+    done: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+impl ProgressHandle {
+    /// Reports progress as a fraction from 0.0 to 1.0
+    pub fn set(&self, fraction: f32) {
+        let permille = (fraction.clamp(0.0, 1.0) * 1000.0) as u32;
+        self.0.permille.store(permille, Ordering::Relaxed);
+        platform::wake();
+    }
+
+    /// The task should stop early once this is true
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn finish(&self) {
+        self.0.done.store(true, Ordering::Relaxed);
+        platform::wake();
+    }
+}
+
+/// Progress bar for a background task, Back cancels it, replies `true` if
+/// the task ran to the end and `false` if it was cancelled
+pub struct Progress {
+    title: &'static str,
+    message: String,
+    handle: ProgressHandle,
+}
+
+impl Progress {
+    /// Runs `task` on its own thread while the dialog is shown
+    pub fn spawn<F>(title: &'static str, message: impl Into<String>, task: F) -> Self
+    where
+        F: FnOnce(&ProgressHandle) + Send + 'static,
+    {
+        let handle = ProgressHandle::default();
+        let worker = handle.clone();
+        thread::spawn(move || {
+            task(&worker);
+            worker.finish();
+        });
+        Self {
+            title,
+            message: message.into(),
+            handle,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.handle.0.done.load(Ordering::Relaxed)
+    }
+}
+
+impl View for Progress {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let frame = draw_frame(display, theme, self.title, 3);
+        let done = self.is_done();
+        let message = if done { "Done" } else { &self.message };
+        draw_message(display, theme, &frame, &[message.to_string()]);
+
+        // Bar with the percentage to its right
+        let permille = self.handle.0.permille.load(Ordering::Relaxed);
+        let percent = format!("{}%", if done { 100 } else { permille / 10 });
+        let bar_width = frame.width - 60;
+        let y = frame.baseline(1);
+        let bar = Rectangle::new(
+            Point::new(frame.left, y - 10),
+            Size::new(bar_width, BAR_HEIGHT),
+        );
+        bar.into_styled(PrimitiveStyle::with_stroke(theme.foreground, 1))
+            .draw(display)
+            .unwrap();
+        let filled = if done {
+            bar_width
+        } else {
+            bar_width * permille / 1000
+        };
+        Rectangle::new(bar.top_left, Size::new(filled, BAR_HEIGHT))
+            .into_styled(PrimitiveStyle::with_fill(theme.highlight))
+            .draw(display)
+            .unwrap();
+        layout::draw_line(
+            display,
+            &percent,
+            FontStyle::new(font::regular(), theme.foreground),
+            Point::new(frame.left + bar_width as i32 + 10, y),
+            50,
+            Align::Right,
+        );
+
+        let button = if done { "OK" } else { "Cancel" };
+        draw_buttons(display, theme, &frame, 2, &[button], 0);
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        match action {
+            Action::Select | Action::Back => ViewResult::PopWith(Reply::new(self.is_done())),
+            Action::Up | Action::Down | Action::Power | Action::Screenshot => ViewResult::None,
+        }
+    }
+
+    fn on_exit(&mut self) {
+        // Cancelled, or popped before the task finished
+        if !self.is_done() {
+            self.handle.0.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn title(&self) -> &str {
+        self.title
+    }
+}
@@ -0,0 +1,390 @@
+//! Host for apps running as their own process, see `apps::external`
+//!
+//! The app prints one command per line on stdout:
+//!
+//! - `title <text>` sets the header
+//! - `clear` starts a drawing, `show` puts it on screen
+//! - `text <x> <y> <colour> <text>` and `small ...` draw text in the
+//!   regular and small font, `y` is the baseline
+//! - `rect <x> <y> <width> <height> <colour>` fills a rectangle
+//! - `line <x1> <y1> <x2> <y2> <colour>` draws a line
+//! - `menu <title>` starts a menu, `item <label>` adds to it and `show`
+//!   puts it on screen, Up and Down then move through it in the pager
+//! - `toast <text>` shows a notice, unless the screen is locked
+//!
+//! Positions are pixels from the top left of the area under the header and
+//! colours are theme names such as `foreground` or `danger`, or `#rrggbb`.
+//!
+//! The pager writes `size <width> <height>` for that area first, then
+//! `key <up|down|select|back>` for each key, or `select <index>` when a
+//! menu item is picked. Back is the app's to handle, pressing it again
+//! before the app has printed anything closes it. Closing stdin asks the
+//! app to quit, it is killed a second later.
+
+use std::path::Path;
+use std::sync::mpsc::TryRecvError;
+use std::time::{Duration, Instant};
+
+use embedded_graphics::{
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::{Line, PrimitiveStyle, Rectangle},
+    text::Text,
+};
+
+use super::font::{self, FontStyle};
+use super::layout::{self, Align};
+use super::lock;
+use super::menu::draw_header;
+use super::theme::parse_color;
+use super::toast;
+use super::{Menu, MenuAction, MenuItem, STATUSBAR_HEIGHT, Theme, View, ViewResult};
+use crate::apps::external::Process;
+use crate::platform::{Action, RenderBuffer};
+
+/// Top of the area apps draw in, just under the header
+const CONTENT_TOP: i32 = STATUSBAR_HEIGHT + 22;
+
+/// How often to look for the exit status of an app that closed stdout
+const EXIT_POLL: Duration = Duration::from_millis(50);
+
+#[derive(Clone)]
+enum Paint {
+    Named(String),
+    Rgb(Rgb888),
+}
+
+impl Paint {
+    fn parse(word: &str) -> Result<Self, String> {
+        if let Some(rgb) = parse_color(word) {
+            return Ok(Paint::Rgb(rgb));
+        }
+        match Theme::dark().named(word) {
+            Some(_) => Ok(Paint::Named(word.to_string())),
+            None => Err(format!("unknown colour {:?}", word)),
+        }
+    }
+
+    fn color(&self, theme: &Theme) -> Rgb888 {
+        match self {
+            Paint::Named(name) => theme.named(name).unwrap_or(theme.foreground),
+            Paint::Rgb(rgb) => *rgb,
+        }
+    }
+}
+
+enum Shape {
+    Text {
+        at: Point,
+        paint: Paint,
+        small: bool,
+        text: String,
+    },
+    Rect {
+        area: Rectangle,
+        paint: Paint,
+    },
+    Line {
+        from: Point,
+        to: Point,
+        paint: Paint,
+    },
+}
+
+/// What the app has put on screen
+enum Screen {
+    Drawing(Vec<Shape>),
+    Menu(Menu),
+}
+
+/// What the app is putting together for its next `show`
+enum Building {
+    Nothing,
+    Drawing(Vec<Shape>),
+    Menu(String, Vec<String>),
+}
+
+pub struct ExternalView {
+    name: String,
+    title: String,
+    process: Option<Process>,
+    screen: Screen,
+    building: Building,
+    /// Back was passed on and the app has not answered yet
+    back_pending: bool,
+    /// Why the app is gone, once it is
+    ended: Option<String>,
+}
+
+impl ExternalView {
+    pub fn new(name: &str, path: &Path) -> Self {
+        let mut view = Self {
+            name: name.to_string(),
+            title: name.to_string(),
+            process: None,
+            screen: Screen::Drawing(Vec::new()),
+            building: Building::Nothing,
+            back_pending: false,
+            ended: None,
+        };
+        match Process::spawn(path) {
+            Ok(process) => {
+                let height = crate::DISPLAY_HEIGHT as i32 - CONTENT_TOP;
+                process.send(format!("size {} {}", crate::DISPLAY_WIDTH, height));
+                view.process = Some(process);
+            }
+            Err(e) => {
+                eprintln!("Error starting {}: {}", path.display(), e);
+                view.ended = Some(format!("could not start: {}", e));
+            }
+        }
+        view
+    }
+
+    fn send(&self, line: String) {
+        if let Some(process) = &self.process {
+            process.send(line);
+        }
+    }
+
+    fn apply(&mut self, line: &str) -> Result<(), String> {
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "title" => self.title = rest.to_string(),
+            "clear" => self.building = Building::Drawing(Vec::new()),
+            "menu" => self.building = Building::Menu(rest.to_string(), Vec::new()),
+            "item" => match &mut self.building {
+                Building::Menu(_, items) => items.push(rest.to_string()),
+                _ => return Err("item outside a menu".into()),
+            },
+            "show" => {
+                self.screen = match std::mem::replace(&mut self.building, Building::Nothing) {
+                    Building::Nothing => return Err("nothing to show".into()),
+                    Building::Drawing(shapes) => Screen::Drawing(shapes),
+                    Building::Menu(title, labels) => Screen::Menu(menu(title, labels)),
+                }
+            }
+            // Nothing from an app reaches the lock screen
+            "toast" if lock::is_locked() => {}
+            "toast" => toast::show(rest),
+            "text" | "small" | "rect" | "line" => {
+                let shape = parse_shape(command, rest)?;
+                match &mut self.building {
+                    Building::Drawing(shapes) => shapes.push(shape),
+                    _ => return Err("drawing outside clear and show".into()),
+                }
+            }
+            _ => return Err("unknown command".into()),
+        }
+        Ok(())
+    }
+
+    /// Back goes to the app, twice in a row closes it
+    fn back(&mut self) -> ViewResult {
+        if self.back_pending {
+            return ViewResult::Pop;
+        }
+        self.back_pending = true;
+        self.send("key back".into());
+        ViewResult::None
+    }
+}
+
+fn menu(title: String, labels: Vec<String>) -> Menu {
+    let items = labels
+        .into_iter()
+        .map(|label| MenuItem {
+            label,
+            action: MenuAction::None,
+        })
+        .collect();
+    Menu::new(title, items)
+}
+
+fn parse_shape(command: &str, args: &str) -> Result<Shape, String> {
+    let number = |word: Option<&str>| -> Result<i32, String> {
+        let word = word.ok_or("missing argument")?;
+        word.parse()
+            .map_err(|_| format!("{:?} is not a number", word))
+    };
+    let paint = |word: Option<&str>| Paint::parse(word.ok_or("missing colour")?);
+    match command {
+        "text" | "small" => {
+            let mut words = args.splitn(4, ' ');
+            let x = number(words.next())?;
+            let y = number(words.next())?;
+            let paint = paint(words.next())?;
+            Ok(Shape::Text {
+                at: Point::new(x, CONTENT_TOP + y),
+                paint,
+                small: command == "small",
+                text: words.next().unwrap_or("").to_string(),
+            })
+        }
+        "rect" => {
+            let mut words = args.split_whitespace();
+            let x = number(words.next())?;
+            let y = number(words.next())?;
+            let width = number(words.next())?.max(0) as u32;
+            let height = number(words.next())?.max(0) as u32;
+            Ok(Shape::Rect {
+                area: Rectangle::new(Point::new(x, CONTENT_TOP + y), Size::new(width, height)),
+                paint: paint(words.next())?,
+            })
+        }
+        _ => {
+            let mut words = args.split_whitespace();
+            let from = Point::new(number(words.next())?, CONTENT_TOP + number(words.next())?);
+            let to = Point::new(number(words.next())?, CONTENT_TOP + number(words.next())?);
+            Ok(Shape::Line {
+                from,
+                to,
+                paint: paint(words.next())?,
+            })
+        }
+    }
+}
+
+impl View for ExternalView {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let width = display.bounding_box().size.width;
+        if let Some(ended) = &self.ended {
+            draw_header(display, theme, &self.title);
+            layout::draw_line(
+                display,
+                &format!("{} has closed ({})", self.name, ended),
+                FontStyle::new(font::regular(), theme.muted),
+                Point::new(10, CONTENT_TOP + 40),
+                width - 20,
+                Align::Center,
+            );
+            return;
+        }
+        match &self.screen {
+            Screen::Menu(menu) => menu.render(display, theme),
+            Screen::Drawing(shapes) => {
+                draw_header(display, theme, &self.title);
+                // Drawing stays clear of the status bar and header
+                let area = Rectangle::new(
+                    Point::new(0, CONTENT_TOP),
+                    Size::new(width, display.bounding_box().size.height),
+                );
+                let mut content = display.clipped(&area);
+                for shape in shapes {
+                    match shape {
+                        Shape::Text {
+                            at,
+                            paint,
+                            small,
+                            text,
+                        } => {
+                            let font = if *small {
+                                font::small()
+                            } else {
+                                font::regular()
+                            };
+                            let style = FontStyle::new(font, paint.color(theme));
+                            Text::new(text, *at, style).draw(&mut content).unwrap();
+                        }
+                        Shape::Rect { area, paint } => area
+                            .into_styled(PrimitiveStyle::with_fill(paint.color(theme)))
+                            .draw(&mut content)
+                            .unwrap(),
+                        Shape::Line { from, to, paint } => Line::new(*from, *to)
+                            .into_styled(PrimitiveStyle::with_stroke(paint.color(theme), 1))
+                            .draw(&mut content)
+                            .unwrap(),
+                    }
+                }
+            }
+        }
+    }
+
+    fn tick(&mut self, now: Instant) -> Option<Instant> {
+        let process = self.process.as_mut()?;
+        let mut lines = Vec::new();
+        let mut closed = false;
+        loop {
+            match process.lines.try_recv() {
+                Ok(line) => lines.push(line),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    closed = true;
+                    break;
+                }
+            }
+        }
+        for line in lines {
+            self.back_pending = false;
+            if let Err(e) = self.apply(line.trim_end()) {
+                eprintln!("{}: ignoring {:?}: {}", self.title, line, e);
+            }
+        }
+        if !closed {
+            return match &mut self.screen {
+                Screen::Menu(menu) => menu.tick(now),
+                Screen::Drawing(_) => None,
+            };
+        }
+        // Stdout closing comes just before the exit, wait to see how it went
+        let process = self.process.as_mut()?;
+        match process.exit_status() {
+            Some(status) => {
+                self.ended = Some(status);
+                self.process.take()?.close();
+                None
+            }
+            None => Some(now + EXIT_POLL),
+        }
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        if self.ended.is_some() {
+            return match action {
+                Action::Select | Action::Back => ViewResult::Pop,
+                _ => ViewResult::None,
+            };
+        }
+        match (&mut self.screen, action) {
+            (_, Action::Back) => self.back(),
+            (_, Action::Power | Action::Screenshot) => ViewResult::None,
+            (Screen::Menu(menu), Action::Select) => {
+                let line = format!("select {}", menu.selected);
+                self.send(line);
+                ViewResult::None
+            }
+            (Screen::Menu(menu), _) => menu.handle(action),
+            (Screen::Drawing(_), _) => {
+                self.send(format!("key {}", action.name()));
+                ViewResult::None
+            }
+        }
+    }
+
+    fn on_exit(&mut self) {
+        if let Some(process) = self.process.take() {
+            process.close();
+        }
+    }
+
+    fn title(&self) -> &str {
+        match &self.screen {
+            Screen::Menu(menu) => &menu.title,
+            Screen::Drawing(_) => &self.title,
+        }
+    }
+
+    fn labels(&self) -> Vec<String> {
+        match &self.screen {
+            Screen::Menu(menu) => menu.labels(),
+            Screen::Drawing(_) => Vec::new(),
+        }
+    }
+
+    fn selected(&self) -> Option<usize> {
+        match &self.screen {
+            Screen::Menu(menu) => Some(menu.selected),
+            Screen::Drawing(_) => None,
+        }
+    }
+}
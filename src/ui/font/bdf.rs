@@ -0,0 +1,106 @@
+//! Glyph Bitmap Distribution Format parser
+
+use std::collections::HashMap;
+
+pub struct Glyph {
+    pub advance: i32,
+    pub width: u32,
+    pub height: u32,
+    pub x_offset: i32,
+    pub y_offset: i32,
+    bitmap: Vec<u8>,
+}
+
+impl Glyph {
+    /// Set pixels relative to the glyph's bounding box top left
+    pub fn pixels(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        let stride = self.width.div_ceil(8) as usize;
+        (0..self.height).flat_map(move |y| {
+            (0..self.width).filter_map(move |x| {
+                let byte = self.bitmap.get(y as usize * stride + x as usize / 8)?;
+                (byte & (0x80 >> (x % 8)) != 0).then_some((x as i32, y as i32))
+            })
+        })
+    }
+}
+
+pub struct BdfFont {
+    /// Pixel height, ascent plus descent
+    pub height: u32,
+    glyphs: HashMap<char, Glyph>,
+}
+
+impl BdfFont {
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c)
+    }
+
+    /// Parses a font, encodings are taken as Unicode code points (ISO10646)
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if !lines.next()?.starts_with("STARTFONT") {
+            return None;
+        }
+
+        let mut ascent = 0;
+        let mut descent = 0;
+        let mut glyphs = HashMap::new();
+        let mut encoding = None;
+        let mut advance = 0;
+        let mut bbx = (0u32, 0u32, 0i32, 0i32);
+
+        while let Some(line) = lines.next() {
+            let mut words = line.split_whitespace();
+            let number = |s: Option<&str>| s.and_then(|v| v.parse::<i32>().ok()).unwrap_or(0);
+            match words.next() {
+                Some("FONT_ASCENT") => ascent = number(words.next()),
+                Some("FONT_DESCENT") => descent = number(words.next()),
+                Some("ENCODING") => {
+                    encoding = words
+                        .next()
+                        .and_then(|v| v.parse::<u32>().ok())
+                        .and_then(char::from_u32);
+                }
+                Some("DWIDTH") => advance = number(words.next()),
+                Some("BBX") => {
+                    let w = number(words.next()).max(0) as u32;
+                    let h = number(words.next()).max(0) as u32;
+                    bbx = (w, h, number(words.next()), number(words.next()));
+                }
+                Some("BITMAP") => {
+                    let mut bitmap = Vec::new();
+                    for row in lines.by_ref() {
+                        let row = row.trim();
+                        if row == "ENDCHAR" {
+                            break;
+                        }
+                        for i in (0..row.len()).step_by(2) {
+                            let byte = row.get(i..i + 2).unwrap_or("0");
+                            bitmap.push(u8::from_str_radix(byte, 16).unwrap_or(0));
+                        }
+                    }
+                    if let Some(c) = encoding.take() {
+                        let (width, height, x_offset, y_offset) = bbx;
+                        glyphs.insert(
+                            c,
+                            Glyph {
+                                advance,
+                                width,
+                                height,
+                                x_offset,
+                                y_offset,
+                                bitmap,
+                            },
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            height: (ascent + descent).max(0) as u32,
+            glyphs,
+        })
+    }
+}
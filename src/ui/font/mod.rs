@@ -0,0 +1,269 @@
+//! Unicode text: BDF fonts from the config directory, falling back to
+//! the built-in ISO 8859 fonts for Latin, Cyrillic and Greek
+
+mod bdf;
+
+use std::sync::OnceLock;
+
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyle, iso_8859_1, iso_8859_2, iso_8859_4, iso_8859_5},
+    mono_font::{iso_8859_7, iso_8859_9, iso_8859_15, mapping, mapping::StrGlyphMapping},
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::Rectangle,
+    text::{
+        Baseline, Text,
+        renderer::{TextMetrics, TextRenderer},
+    },
+};
+
+use crate::config;
+use bdf::BdfFont;
+
+type Builtin = (
+    &'static MonoFont<'static>,
+    &'static StrGlyphMapping<'static>,
+);
+
+pub struct Font {
+    bdf: Vec<BdfFont>,
+    builtin: [Builtin; 7],
+    height: u32,
+    baseline: u32,
+}
+
+enum Glyph<'a> {
+    Bdf(&'a bdf::Glyph),
+    Builtin(&'static MonoFont<'static>),
+}
+
+macro_rules! builtin {
+    ($font:ident) => {
+        [
+            (&iso_8859_1::$font, &mapping::ISO_8859_1),
+            (&iso_8859_2::$font, &mapping::ISO_8859_2),
+            (&iso_8859_4::$font, &mapping::ISO_8859_4),
+            (&iso_8859_9::$font, &mapping::ISO_8859_9),
+            (&iso_8859_15::$font, &mapping::ISO_8859_15),
+            (&iso_8859_5::$font, &mapping::ISO_8859_5),
+            (&iso_8859_7::$font, &mapping::ISO_8859_7),
+        ]
+    };
+}
+
+impl Font {
+    fn new(builtin: [Builtin; 7]) -> Self {
+        let size = builtin[0].0.character_size;
+        Self {
+            bdf: Vec::new(),
+            height: size.height,
+            baseline: builtin[0].0.baseline,
+            builtin,
+        }
+    }
+
+    pub fn line_height(&self) -> u32 {
+        self.height
+    }
+
+    /// Whether `c` has a real glyph rather than the replacement
+    pub fn contains(&self, c: char) -> bool {
+        self.bdf.iter().any(|f| f.glyph(c).is_some())
+            || self.builtin.iter().any(|(_, mapping)| mapping.contains(c))
+    }
+
+    fn glyph(&self, c: char) -> Glyph<'_> {
+        if let Some(glyph) = self.bdf.iter().find_map(|f| f.glyph(c)) {
+            return Glyph::Bdf(glyph);
+        }
+        // Latin-1 is by far the most common, skip the mapping scan
+        let code = c as u32;
+        if (0x20..0x7F).contains(&code) || (0xA0..=0xFF).contains(&code) {
+            return Glyph::Builtin(self.builtin[0].0);
+        }
+        let font = self
+            .builtin
+            .iter()
+            .find(|(_, mapping)| mapping.contains(c))
+            .map_or(self.builtin[0].0, |(font, _)| *font);
+        Glyph::Builtin(font)
+    }
+
+    fn advance(&self, c: char) -> i32 {
+        match self.glyph(c) {
+            Glyph::Bdf(glyph) => glyph.advance,
+            Glyph::Builtin(font) => (font.character_size.width + font.character_spacing) as i32,
+        }
+    }
+
+    /// Width of `text` in pixels
+    pub fn measure(&self, text: &str) -> u32 {
+        text.chars().map(|c| self.advance(c)).sum::<i32>().max(0) as u32
+    }
+}
+
+struct Fonts {
+    regular: Font,
+    small: Font,
+}
+
+static FONTS: OnceLock<Fonts> = OnceLock::new();
+
+fn fonts() -> &'static Fonts {
+    FONTS.get_or_init(|| {
+        let mut fonts = Fonts {
+            regular: Font::new(builtin!(FONT_10X20)),
+            small: Font::new(builtin!(FONT_9X15_BOLD)),
+        };
+        load_bdf(&mut fonts);
+        fonts
+    })
+}
+
+/// Reads `fonts/*.bdf`, each file serves the size closest to its height
+/// and files are tried in name order before the built-in glyphs
+fn load_bdf(fonts: &mut Fonts) {
+    let Ok(entries) = std::fs::read_dir(config::path("fonts")) else {
+        return;
+    };
+    let mut paths: Vec<_> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "bdf"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let Some(font) = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| BdfFont::parse(&text))
+        else {
+            eprintln!("Error loading font {}", path.display());
+            continue;
+        };
+        let to_regular = font.height.abs_diff(fonts.regular.height);
+        let to_small = font.height.abs_diff(fonts.small.height);
+        if to_regular <= to_small {
+            fonts.regular.bdf.push(font);
+        } else {
+            fonts.small.bdf.push(font);
+        }
+    }
+}
+
+/// Loads fonts up front instead of on the first drawn frame
+pub fn load() {
+    fonts();
+}
+
+/// Menu and body text
+pub fn regular() -> &'static Font {
+    &fonts().regular
+}
+
+/// Status bar text
+pub fn small() -> &'static Font {
+    &fonts().small
+}
+
+/// Character style for `Text`, drawing with a `Font` in one color
+#[derive(Clone, Copy)]
+pub struct FontStyle {
+    font: &'static Font,
+    color: Rgb888,
+}
+
+impl FontStyle {
+    pub fn new(font: &'static Font, color: Rgb888) -> Self {
+        Self { font, color }
+    }
+
+    pub fn font(&self) -> &'static Font {
+        self.font
+    }
+
+    /// Vertical offset from the requested baseline to the alphabetic one
+    fn baseline_offset(&self, baseline: Baseline) -> i32 {
+        let font = self.font;
+        match baseline {
+            Baseline::Top => font.baseline as i32,
+            Baseline::Bottom => font.baseline as i32 - font.height as i32 + 1,
+            Baseline::Middle => font.baseline as i32 - (font.height as i32 - 1) / 2,
+            Baseline::Alphabetic => 0,
+        }
+    }
+}
+
+impl TextRenderer for FontStyle {
+    type Color = Rgb888;
+
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let mut cursor = position + Point::new(0, self.baseline_offset(baseline));
+        let builtin_style = |font| MonoTextStyle::new(font, self.color);
+        let mut buf = [0u8; 4];
+
+        for c in text.chars() {
+            match self.font.glyph(c) {
+                Glyph::Bdf(glyph) => {
+                    let top_left = cursor
+                        + Point::new(glyph.x_offset, -(glyph.height as i32) - glyph.y_offset);
+                    target.draw_iter(
+                        glyph
+                            .pixels()
+                            .map(|(x, y)| Pixel(top_left + Point::new(x, y), self.color)),
+                    )?;
+                    cursor.x += glyph.advance;
+                }
+                Glyph::Builtin(font) => {
+                    cursor = Text::with_baseline(
+                        c.encode_utf8(&mut buf),
+                        cursor,
+                        builtin_style(font),
+                        Baseline::Alphabetic,
+                    )
+                    .draw(target)?;
+                }
+            }
+        }
+
+        Ok(cursor - Point::new(0, self.baseline_offset(baseline)))
+    }
+
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        _baseline: Baseline,
+        _target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        Ok(position + Point::new(width as i32, 0))
+    }
+
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let width = self.font.measure(text);
+        let top = position.y + self.baseline_offset(baseline) - self.font.baseline as i32;
+        TextMetrics {
+            bounding_box: Rectangle::new(
+                Point::new(position.x, top),
+                Size::new(width, self.font.height),
+            ),
+            next_position: position + Point::new(width as i32, 0),
+        }
+    }
+
+    fn line_height(&self) -> u32 {
+        self.font.height
+    }
+}
@@ -4,25 +4,31 @@ use embedded_graphics::{
 };
 
 use crate::platform::RenderBuffer;
-use crate::ui::colors::*;
+use crate::ui::Theme;
 
 const WIDTH: i32 = 21; // 18 body + 3 tip
 const SEGMENT_COUNT: u8 = 3;
 
 /// Draws battery icon ending at cursor position, returns width
-pub fn draw(display: &mut RenderBuffer, cursor: i32, battery: u8, charging: bool) -> i32 {
+pub fn draw(
+    display: &mut RenderBuffer,
+    theme: &Theme,
+    cursor: i32,
+    battery: u8,
+    charging: bool,
+) -> i32 {
     let x = cursor - WIDTH;
     let y_offset = 8;
 
     // Battery outline
     Rectangle::new(Point::new(x, y_offset), Size::new(18, 10))
-        .into_styled(PrimitiveStyle::with_stroke(FOREGROUND, 1))
+        .into_styled(PrimitiveStyle::with_stroke(theme.statusbar_text, 1))
         .draw(display)
         .unwrap();
 
     // Battery tip
     Rectangle::new(Point::new(x + 18, y_offset + 3), Size::new(3, 4))
-        .into_styled(PrimitiveStyle::with_fill(FOREGROUND))
+        .into_styled(PrimitiveStyle::with_fill(theme.statusbar_text))
         .draw(display)
         .unwrap();
 
@@ -40,14 +46,14 @@ pub fn draw(display: &mut RenderBuffer, cursor: i32, battery: u8, charging: bool
         let seg_x = x + 2 + (i as i32 * (segment_width as i32 + segment_gap));
         let color = if i < filled {
             if battery <= 20 {
-                RED
+                theme.danger
             } else if battery == 100 {
-                CYAN
+                theme.info
             } else {
-                GREEN
+                theme.success
             }
         } else {
-            BACKGROUND
+            theme.background
         };
 
         Rectangle::new(Point::new(seg_x, y_offset + 2), Size::new(segment_width, 6))
@@ -58,13 +64,13 @@ pub fn draw(display: &mut RenderBuffer, cursor: i32, battery: u8, charging: bool
 
     // Charging bolt (with dark outline for contrast)
     if charging {
-        draw_bolt(display, x, y_offset);
+        draw_bolt(display, theme, x, y_offset);
     }
 
     WIDTH
 }
 
-fn draw_bolt(display: &mut RenderBuffer, x: i32, y_offset: i32) {
+fn draw_bolt(display: &mut RenderBuffer, theme: &Theme, x: i32, y_offset: i32) {
     // Dark outline
     for dx in -1i32..=1 {
         for dy in -1i32..=1 {
@@ -73,21 +79,21 @@ fn draw_bolt(display: &mut RenderBuffer, x: i32, y_offset: i32) {
                     Point::new(x + 10 + dx, y_offset + dy),
                     Point::new(x + 7 + dx, y_offset + 5 + dy),
                 )
-                .into_styled(PrimitiveStyle::with_stroke(BACKGROUND, 1))
+                .into_styled(PrimitiveStyle::with_stroke(theme.background, 1))
                 .draw(display)
                 .unwrap();
                 Line::new(
                     Point::new(x + 7 + dx, y_offset + 5 + dy),
                     Point::new(x + 11 + dx, y_offset + 5 + dy),
                 )
-                .into_styled(PrimitiveStyle::with_stroke(BACKGROUND, 1))
+                .into_styled(PrimitiveStyle::with_stroke(theme.background, 1))
                 .draw(display)
                 .unwrap();
                 Line::new(
                     Point::new(x + 11 + dx, y_offset + 5 + dy),
                     Point::new(x + 8 + dx, y_offset + 10 + dy),
                 )
-                .into_styled(PrimitiveStyle::with_stroke(BACKGROUND, 1))
+                .into_styled(PrimitiveStyle::with_stroke(theme.background, 1))
                 .draw(display)
                 .unwrap();
             }
@@ -98,21 +104,21 @@ fn draw_bolt(display: &mut RenderBuffer, x: i32, y_offset: i32) {
         Point::new(x + 10, y_offset),
         Point::new(x + 7, y_offset + 5),
     )
-    .into_styled(PrimitiveStyle::with_stroke(YELLOW, 1))
+    .into_styled(PrimitiveStyle::with_stroke(theme.warning, 1))
     .draw(display)
     .unwrap();
     Line::new(
         Point::new(x + 7, y_offset + 5),
         Point::new(x + 11, y_offset + 5),
     )
-    .into_styled(PrimitiveStyle::with_stroke(YELLOW, 1))
+    .into_styled(PrimitiveStyle::with_stroke(theme.warning, 1))
     .draw(display)
     .unwrap();
     Line::new(
         Point::new(x + 11, y_offset + 5),
         Point::new(x + 8, y_offset + 10),
     )
-    .into_styled(PrimitiveStyle::with_stroke(YELLOW, 1))
+    .into_styled(PrimitiveStyle::with_stroke(theme.warning, 1))
     .draw(display)
     .unwrap();
 }
@@ -0,0 +1,54 @@
+use embedded_graphics::{
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+use crate::platform::RenderBuffer;
+
+/// One-colour picture as rows of `#` (set) and anything else (clear), so
+/// apps can draw theirs as text
+pub struct Icon(pub &'static [&'static str]);
+
+impl Icon {
+    /// Plain tile for apps without an icon of their own
+    pub const DEFAULT: Icon = Icon(&[
+        "................",
+        "..############..",
+        ".##..........##.",
+        ".#............#.",
+        ".#............#.",
+        ".#....####....#.",
+        ".#...##..##...#.",
+        ".#...#....#...#.",
+        ".#...#....#...#.",
+        ".#...##..##...#.",
+        ".#....####....#.",
+        ".#............#.",
+        ".#............#.",
+        ".##..........##.",
+        "..############..",
+        "................",
+    ]);
+
+    pub fn width(&self) -> u32 {
+        self.0.iter().map(|row| row.len()).max().unwrap_or(0) as u32
+    }
+
+    /// Draws with each dot `scale` pixels square, `top_left` at the corner
+    pub fn draw(&self, display: &mut RenderBuffer, top_left: Point, scale: u32, color: Rgb888) {
+        let style = PrimitiveStyle::with_fill(color);
+        for (y, row) in self.0.iter().enumerate() {
+            for (x, dot) in row.bytes().enumerate() {
+                if dot != b'#' {
+                    continue;
+                }
+                let corner = top_left + Point::new(x as i32, y as i32) * scale as i32;
+                Rectangle::new(corner, Size::new_equal(scale))
+                    .into_styled(style)
+                    .draw(display)
+                    .unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use embedded_graphics::{
+    prelude::*,
+    primitives::{Circle, PrimitiveStyle},
+};
+
+use crate::platform::RenderBuffer;
+use crate::ui::Theme;
+
+const WIDTH: i32 = 12;
+
+/// Draws a crescent moon ending at cursor position, returns width
+pub fn draw(display: &mut RenderBuffer, theme: &Theme, cursor: i32, active: bool) -> i32 {
+    let x = cursor - WIDTH;
+    let y_offset = 6;
+    let color = if active {
+        theme.statusbar_text
+    } else {
+        theme.muted
+    };
+
+    Circle::new(Point::new(x, y_offset), WIDTH as u32)
+        .into_styled(PrimitiveStyle::with_fill(color))
+        .draw(display)
+        .unwrap();
+
+    // Cut the crescent out with the status bar background
+    Circle::new(Point::new(x + 4, y_offset - 2), WIDTH as u32 - 1)
+        .into_styled(PrimitiveStyle::with_fill(theme.statusbar))
+        .draw(display)
+        .unwrap();
+
+    WIDTH
+}
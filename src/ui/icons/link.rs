@@ -0,0 +1,40 @@
+use embedded_graphics::{
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+use crate::platform::RenderBuffer;
+use crate::ui::Theme;
+
+const BARS: i32 = 3;
+const BAR_WIDTH: i32 = 3;
+const BAR_GAP: i32 = 1;
+const WIDTH: i32 = BARS * (BAR_WIDTH + BAR_GAP) - BAR_GAP;
+
+/// Draws rising signal bars ending at cursor position, returns width
+///
+/// All bars are lit while connected, only the first one dimmed otherwise.
+pub fn draw(display: &mut RenderBuffer, theme: &Theme, cursor: i32, connected: bool) -> i32 {
+    let x = cursor - WIDTH;
+    let bottom = 18;
+    for i in 0..BARS {
+        let lit = connected || i == 0;
+        let color = if connected {
+            theme.statusbar_text
+        } else {
+            theme.muted
+        };
+        let height = 4 + i * 4;
+        let bar = Rectangle::new(
+            Point::new(x + i * (BAR_WIDTH + BAR_GAP), bottom - height),
+            Size::new(BAR_WIDTH as u32, height as u32),
+        );
+        let style = if lit {
+            PrimitiveStyle::with_fill(color)
+        } else {
+            PrimitiveStyle::with_stroke(color, 1)
+        };
+        bar.into_styled(style).draw(display).unwrap();
+    }
+    WIDTH
+}
@@ -1 +1,6 @@
 pub mod battery;
+mod bitmap;
+pub mod dnd;
+pub mod link;
+
+pub use bitmap::Icon;
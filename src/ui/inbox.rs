@@ -0,0 +1,117 @@
+use std::time::Instant;
+
+use embedded_graphics::prelude::*;
+
+use super::font::{self, FontStyle};
+use super::layout::{self, Align};
+use super::menu::ITEM_START_Y;
+use super::message::MessageView;
+use super::{Menu, MenuAction, MenuItem, Theme, View, ViewResult};
+use crate::inbox::{self, Message};
+use crate::platform::{Action, Priority, RenderBuffer};
+
+/// Marker of read and resolved messages
+const NO_MARKER: &str = "  ";
+
+/// Message list, opening a message acknowledges it
+pub struct InboxView {
+    menu: Menu,
+    messages: Vec<Message>,
+}
+
+impl InboxView {
+    pub fn new() -> Self {
+        let mut view = Self {
+            menu: Menu::new("Inbox", Vec::new()),
+            messages: Vec::new(),
+        };
+        view.refresh();
+        view
+    }
+
+    fn refresh(&mut self) {
+        self.messages = inbox::messages();
+        self.menu.items = self
+            .messages
+            .iter()
+            .map(|m| MenuItem {
+                label: label(m),
+                action: MenuAction::None,
+            })
+            .collect();
+        // Resolved messages are struck through
+        self.menu.struck = self
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.resolved.is_some())
+            .map(|(i, _)| i)
+            .collect();
+        self.menu.selected = self
+            .menu
+            .selected
+            .min(self.messages.len().saturating_sub(1));
+    }
+}
+
+fn label(message: &Message) -> String {
+    let marker = match (message.is_pending(), message.priority >= Priority::High) {
+        (false, _) => NO_MARKER,
+        (true, true) => "! ",
+        (true, false) => "* ",
+    };
+    match message.count {
+        1 => format!("{}{}", marker, message.subject),
+        count => format!("{}{} x{}", marker, message.subject, count),
+    }
+}
+
+impl View for InboxView {
+    fn render(&self, buffer: &mut RenderBuffer, theme: &Theme) {
+        self.menu.render(buffer, theme);
+        if self.messages.is_empty() {
+            let width = buffer.bounding_box().size.width;
+            layout::draw_line(
+                buffer,
+                "No messages",
+                FontStyle::new(font::regular(), theme.muted),
+                Point::new(10, ITEM_START_Y),
+                width - 20,
+                Align::Center,
+            );
+        }
+    }
+
+    fn title(&self) -> &str {
+        &self.menu.title
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.messages.iter().map(|m| m.subject.clone()).collect()
+    }
+
+    fn selected(&self) -> Option<usize> {
+        Some(self.menu.selected)
+    }
+
+    fn tick(&mut self, now: Instant) -> Option<Instant> {
+        // Pick up messages that arrived since the last frame
+        self.refresh();
+        self.menu.tick(now)
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        let result = match action {
+            Action::Select => match self.messages.get(self.menu.selected) {
+                Some(message) => {
+                    inbox::acknowledge(message.id);
+                    ViewResult::Push(Box::new(MessageView::new(message.clone())))
+                }
+                None => ViewResult::None,
+            },
+            _ => self.menu.handle(action),
+        };
+        self.refresh();
+        result
+    }
+}
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+use super::dialog::Confirm;
+use super::font::{self, FontStyle};
+use super::layout::{self, Align};
+use super::menu::draw_header;
+use super::{Reply, STATUSBAR_HEIGHT, Theme, View, ViewResult};
+use crate::apps::{self, App};
+use crate::platform::{Action, RenderBuffer};
+
+const COLUMNS: usize = 4;
+const TILE_HEIGHT: i32 = 88;
+const GRID_TOP: i32 = STATUSBAR_HEIGHT + 22;
+/// Icon dots are drawn this many pixels square
+const ICON_SCALE: u32 = 3;
+
+/// Grid of the registered apps, Up and Down step through them in reading
+/// order and Back offers to quit
+pub struct Launcher {
+    apps: Vec<Arc<dyn App>>,
+    selected: usize,
+    /// First row shown
+    scroll: usize,
+}
+
+impl Launcher {
+    pub fn new() -> Self {
+        Self {
+            apps: apps::all(),
+            selected: 0,
+            scroll: 0,
+        }
+    }
+
+    fn visible_rows(height: u32) -> usize {
+        ((height as i32 - GRID_TOP) / TILE_HEIGHT).max(1) as usize
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        // Height is only known while rendering, assume the display's
+        let rows = Self::visible_rows(crate::DISPLAY_HEIGHT);
+        let row = index / COLUMNS;
+        if row < self.scroll {
+            self.scroll = row;
+        } else if row >= self.scroll + rows {
+            self.scroll = row + 1 - rows;
+        }
+    }
+}
+
+impl View for Launcher {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let bounds = display.bounding_box();
+        let tile_width = bounds.size.width as i32 / COLUMNS as i32;
+        draw_header(display, theme, "Apps");
+
+        let rows = Self::visible_rows(bounds.size.height);
+        let first = self.scroll * COLUMNS;
+        let shown = self
+            .apps
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(rows * COLUMNS);
+        for (i, app) in shown {
+            let column = (i % COLUMNS) as i32;
+            let row = (i / COLUMNS - self.scroll) as i32;
+            let left = column * tile_width;
+            let top = GRID_TOP + row * TILE_HEIGHT;
+
+            let color = if i == self.selected {
+                Rectangle::new(
+                    Point::new(left + 4, top + 2),
+                    Size::new(tile_width as u32 - 8, TILE_HEIGHT as u32 - 4),
+                )
+                .into_styled(PrimitiveStyle::with_fill(theme.highlight))
+                .draw(display)
+                .unwrap();
+                theme.highlight_text
+            } else {
+                theme.foreground
+            };
+
+            let icon = app.icon();
+            let icon_width = (icon.width() * ICON_SCALE) as i32;
+            let icon_top = Point::new(left + (tile_width - icon_width) / 2, top + 8);
+            icon.draw(display, icon_top, ICON_SCALE, color);
+
+            layout::draw_line(
+                display,
+                app.name(),
+                FontStyle::new(font::regular(), color),
+                Point::new(left + 6, top + TILE_HEIGHT - 12),
+                tile_width as u32 - 12,
+                Align::Center,
+            );
+        }
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        match action {
+            Action::Up if self.selected > 0 => self.select(self.selected - 1),
+            Action::Down if self.selected + 1 < self.apps.len() => self.select(self.selected + 1),
+            Action::Select => {
+                if let Some(app) = self.apps.get(self.selected) {
+                    return ViewResult::Push(app.open());
+                }
+            }
+            Action::Back => {
+                return ViewResult::Push(Box::new(Confirm::new("Exit", "Quit OpenPager?")));
+            }
+            _ => {}
+        }
+        ViewResult::None
+    }
+
+    fn on_resume(&mut self, reply: Option<Reply>) -> ViewResult {
+        // Answer to the confirmation pushed by Back
+        match reply.map(Reply::downcast::<bool>) {
+            Some(Ok(true)) => ViewResult::Exit,
+            _ => ViewResult::None,
+        }
+    }
+
+    fn title(&self) -> &str {
+        "Apps"
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.apps.iter().map(|app| app.name().to_string()).collect()
+    }
+
+    fn selected(&self) -> Option<usize> {
+        Some(self.selected)
+    }
+}
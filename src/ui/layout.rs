@@ -0,0 +1,94 @@
+//! Measuring, truncating, wrapping and aligning text
+
+use std::borrow::Cow;
+
+use embedded_graphics::{prelude::*, text::Text};
+
+use super::font::{Font, FontStyle};
+use crate::platform::RenderBuffer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// "…" when the font has it, three dots otherwise
+pub fn ellipsis(font: &Font) -> &'static str {
+    if font.contains('…') { "…" } else { "..." }
+}
+
+/// Cuts `text` to fit `width` pixels, ending in an ellipsis when shortened
+pub fn truncate<'a>(font: &Font, text: &'a str, width: u32) -> Cow<'a, str> {
+    if font.measure(text) <= width {
+        return Cow::Borrowed(text);
+    }
+    let ellipsis = ellipsis(font);
+    let budget = width.saturating_sub(font.measure(ellipsis));
+    let mut used = 0;
+    let mut end = 0;
+    for (i, c) in text.char_indices() {
+        used += font.measure(c.encode_utf8(&mut [0; 4]));
+        if used > budget {
+            break;
+        }
+        end = i + c.len_utf8();
+    }
+    Cow::Owned(format!("{}{}", text[..end].trim_end(), ellipsis))
+}
+
+/// Breaks `text` into lines no wider than `width`, preferring spaces and
+/// splitting words that do not fit on a line of their own
+pub fn wrap(font: &Font, text: &str, width: u32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if font.measure(&candidate) <= width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for c in word.chars() {
+                line.push(c);
+                if font.measure(&line) > width && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Draws one line inside `width` pixels from `origin`, truncating as needed,
+/// `origin.y` is the alphabetic baseline
+pub fn draw_line(
+    display: &mut RenderBuffer,
+    text: &str,
+    style: FontStyle,
+    origin: Point,
+    width: u32,
+    align: Align,
+) {
+    let font = style.font();
+    let text = truncate(font, text, width);
+    let slack = width.saturating_sub(font.measure(&text)) as i32;
+    let x = match align {
+        Align::Left => origin.x,
+        Align::Center => origin.x + slack / 2,
+        Align::Right => origin.x + slack,
+    };
+    Text::new(&text, Point::new(x, origin.y), style)
+        .draw(display)
+        .unwrap();
+}
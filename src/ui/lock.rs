@@ -0,0 +1,240 @@
+//! Lock screen, unlocked with a sequence of Up/Down/Select presses set
+//! under Settings > Security
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+use super::dialog;
+use super::font::{self, FontStyle};
+use super::layout::{self, Align};
+use super::segments;
+use super::{Reply, STATUSBAR_HEIGHT, Theme, View, ViewResult};
+use crate::inbox;
+use crate::platform::{Action, RenderBuffer, clock, read_battery};
+use crate::settings;
+
+const CLOCK_HEIGHT: u32 = 72;
+const MIN_CODE: usize = 4;
+const MAX_CODE: usize = 8;
+
+static LOCKED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the lock screen is on the view stack
+pub fn is_locked() -> bool {
+    LOCKED.load(Ordering::Relaxed)
+}
+
+/// When the screen locks for inactivity since `last_input`, `None` when
+/// that is turned off or it already is locked
+pub fn deadline(last_input: Instant) -> Option<Instant> {
+    let minutes = settings::get_int("lock.timeout");
+    if minutes <= 0 || is_locked() {
+        return None;
+    }
+    Some(last_input + Duration::from_secs(minutes as u64 * 60))
+}
+
+/// Letter a button stands for in a stored code
+fn code_char(action: Action) -> Option<char> {
+    match action {
+        Action::Up => Some('U'),
+        Action::Down => Some('D'),
+        Action::Select => Some('S'),
+        Action::Back | Action::Power | Action::Screenshot => None,
+    }
+}
+
+/// Clock, battery and how many alerts are waiting, but nothing of what
+/// they say
+pub struct LockView {
+    entered: String,
+    wrong: bool,
+}
+
+impl LockView {
+    pub fn new() -> Self {
+        Self {
+            entered: String::new(),
+            wrong: false,
+        }
+    }
+}
+
+impl View for LockView {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let bounds = display.bounding_box();
+        let width = bounds.size.width;
+        Rectangle::new(
+            Point::new(0, STATUSBAR_HEIGHT),
+            Size::new(width, bounds.size.height - STATUSBAR_HEIGHT as u32),
+        )
+        .into_styled(PrimitiveStyle::with_fill(theme.background))
+        .draw(display)
+        .unwrap();
+
+        let now = clock::local_time();
+        let time = format!("{:02}:{:02}", now.hour, now.minute);
+        let x = (width - segments::measure(&time, CLOCK_HEIGHT)) as i32 / 2;
+        segments::draw(
+            display,
+            &time,
+            Point::new(x, STATUSBAR_HEIGHT + 20),
+            CLOCK_HEIGHT,
+            theme.foreground,
+        );
+
+        let (battery, charging) = read_battery();
+        let pending = inbox::pending();
+        let summary = format!(
+            "Battery {}%{}  ·  {} pending alert{}",
+            battery,
+            if charging { " charging" } else { "" },
+            pending,
+            if pending == 1 { "" } else { "s" },
+        );
+        let y = STATUSBAR_HEIGHT + 20 + CLOCK_HEIGHT as i32 + 36;
+        let color = if pending > 0 {
+            theme.warning
+        } else {
+            theme.muted
+        };
+        layout::draw_line(
+            display,
+            &summary,
+            FontStyle::new(font::regular(), color),
+            Point::new(10, y),
+            width - 20,
+            Align::Center,
+        );
+
+        let (hint, color) = if settings::get_text("lock.code").is_empty() {
+            ("Press Select to unlock".to_string(), theme.muted)
+        } else if self.wrong {
+            ("Wrong code".to_string(), theme.danger)
+        } else if self.entered.is_empty() {
+            ("Enter code to unlock".to_string(), theme.muted)
+        } else {
+            ("*".repeat(self.entered.len()), theme.foreground)
+        };
+        layout::draw_line(
+            display,
+            &hint,
+            FontStyle::new(font::regular(), color),
+            Point::new(10, y + 30),
+            width - 20,
+            Align::Center,
+        );
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        let code = settings::get_text("lock.code");
+        if code.is_empty() {
+            return match action {
+                Action::Select => ViewResult::Pop,
+                _ => ViewResult::None,
+            };
+        }
+        if action == Action::Back {
+            self.entered.clear();
+            return ViewResult::None;
+        }
+        let Some(c) = code_char(action) else {
+            return ViewResult::None;
+        };
+        self.entered.push(c);
+        self.wrong = false;
+        if self.entered == code {
+            return ViewResult::Pop;
+        }
+        if self.entered.len() >= code.len() {
+            self.entered.clear();
+            self.wrong = true;
+        }
+        ViewResult::None
+    }
+
+    fn title(&self) -> &str {
+        "Locked"
+    }
+
+    fn on_enter(&mut self) {
+        LOCKED.store(true, Ordering::Relaxed);
+    }
+
+    fn on_exit(&mut self) {
+        LOCKED.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Records a new code and replies with it as a `String`, Back right away
+/// replies with an empty one to remove the code
+pub struct SetCode {
+    title: &'static str,
+    entered: String,
+}
+
+impl SetCode {
+    pub fn new(title: &'static str) -> Self {
+        Self {
+            title,
+            entered: String::new(),
+        }
+    }
+}
+
+impl View for SetCode {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let message = format!(
+            "Press Up, Down and Select {} to {} times, then Back. \
+             Back right away removes the code.",
+            MIN_CODE, MAX_CODE
+        );
+        let lines = layout::wrap(font::regular(), &message, dialog::body_width(display));
+        let frame = dialog::draw_frame(display, theme, self.title, lines.len() + 1);
+        dialog::draw_message(display, theme, &frame, &lines);
+
+        let stars = "*".repeat(self.entered.len());
+        let color = if self.entered.len() < MIN_CODE {
+            theme.muted
+        } else {
+            theme.success
+        };
+        layout::draw_line(
+            display,
+            &stars,
+            FontStyle::new(font::regular(), color),
+            Point::new(frame.left, frame.baseline(lines.len())),
+            frame.width,
+            Align::Left,
+        );
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        match code_char(action) {
+            Some(c) if self.entered.len() < MAX_CODE => self.entered.push(c),
+            Some(_) => {}
+            None if action == Action::Back => {
+                return match self.entered.len() {
+                    0 => ViewResult::PopWith(Reply::new(String::new())),
+                    n if n < MIN_CODE => ViewResult::Pop,
+                    _ => ViewResult::PopWith(Reply::new(self.entered.clone())),
+                };
+            }
+            None => {}
+        }
+        ViewResult::None
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn title(&self) -> &str {
+        self.title
+    }
+}
@@ -1,47 +1,154 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
 use embedded_graphics::{
-    mono_font::{MonoTextStyle, ascii::FONT_10X20},
     prelude::*,
-    primitives::{PrimitiveStyle, Rectangle, Triangle},
+    primitives::{Line, PrimitiveStyle, Rectangle, Triangle},
     text::Text,
 };
 
-use super::colors::*;
-use super::{STATUSBAR_HEIGHT, View, ViewResult};
+use super::command::CommandView;
+use super::dialog::Confirm;
+use super::font::{self, FontStyle};
+use super::layout::{self, Align};
+use super::{Reply, STATUSBAR_HEIGHT, Theme, View, ViewResult};
 use crate::platform::{Action, RenderBuffer};
+use crate::shell;
 
 pub struct MenuItem {
-    pub label: &'static str,
+    pub label: String,
     pub action: MenuAction,
 }
 
+#[derive(Clone)]
 pub enum MenuAction {
     SubMenu(fn() -> Menu),
+    Open(fn() -> Box<dyn View>),
+    Run(fn()),
+    /// Like `Open`, for views built from data such as the menu file
+    Build(Rc<dyn Fn() -> Box<dyn View>>),
+    /// Shell command run in the background, a toast says how it went
+    Shell(String),
+    /// Shell command whose output is shown as it runs, killed after
+    /// `timeout` or on Back
+    Output {
+        command: String,
+        timeout: Option<Duration>,
+    },
     Exit,
     None,
 }
 
-const ITEM_HEIGHT: i32 = 22;
+pub(super) const ITEM_HEIGHT: i32 = 22;
 const TITLE_Y: i32 = STATUSBAR_HEIGHT + 16;
 const SEPARATOR_Y: i32 = STATUSBAR_HEIGHT + 20;
-const ITEM_START_Y: i32 = STATUSBAR_HEIGHT + 38;
+pub(super) const ITEM_START_Y: i32 = STATUSBAR_HEIGHT + 38;
+
+/// Marquee timing for an overflowing selected label
+const MARQUEE_PAUSE_MS: u128 = 1200;
+const MARQUEE_PX_PER_SEC: u128 = 40;
+
+/// Horizontal scroll of a label `overflow` pixels too wide: hold at the
+/// start, scroll to the end, hold there, then start over
+fn marquee_offset(elapsed_ms: u128, overflow: u32) -> i32 {
+    let scroll_ms = overflow as u128 * 1000 / MARQUEE_PX_PER_SEC;
+    let t = elapsed_ms % (MARQUEE_PAUSE_MS * 2 + scroll_ms);
+    let scrolled = t.saturating_sub(MARQUEE_PAUSE_MS) * MARQUEE_PX_PER_SEC / 1000;
+    scrolled.min(overflow as u128) as i32
+}
+
+/// Milliseconds until the marquee moves again, sleeping through the holds
+fn marquee_next(elapsed_ms: u128, overflow: u32) -> u128 {
+    let scroll_ms = overflow as u128 * 1000 / MARQUEE_PX_PER_SEC;
+    let cycle = MARQUEE_PAUSE_MS * 2 + scroll_ms;
+    let t = elapsed_ms % cycle;
+    if t < MARQUEE_PAUSE_MS {
+        MARQUEE_PAUSE_MS - t
+    } else if t < MARQUEE_PAUSE_MS + scroll_ms {
+        1000 / MARQUEE_PX_PER_SEC
+    } else {
+        cycle - t
+    }
+}
+
+/// Clears the area below the status bar and draws a titled header
+pub(super) fn draw_header(display: &mut RenderBuffer, theme: &Theme, title: &str) {
+    let bounds = display.bounding_box();
+    let width = bounds.size.width;
+
+    // Clear background (below status bar)
+    Rectangle::new(
+        Point::new(0, STATUSBAR_HEIGHT),
+        Size::new(width, bounds.size.height - STATUSBAR_HEIGHT as u32),
+    )
+    .into_styled(PrimitiveStyle::with_fill(theme.background))
+    .draw(display)
+    .unwrap();
+
+    // Draw title
+    let title_style = FontStyle::new(font::regular(), theme.title);
+    layout::draw_line(
+        display,
+        title,
+        title_style,
+        Point::new(10, TITLE_Y),
+        width - 20,
+        Align::Left,
+    );
+
+    // Draw separator line
+    Rectangle::new(Point::new(0, SEPARATOR_Y), Size::new(width, 2))
+        .into_styled(PrimitiveStyle::with_fill(theme.separator))
+        .draw(display)
+        .unwrap();
+}
+
+/// Strike-through line above the label baseline
+const STRIKE_HEIGHT: i32 = 5;
 
 pub struct Menu {
-    pub title: &'static str,
+    pub title: String,
     pub items: Vec<MenuItem>,
+    /// Indices of items drawn struck through
+    pub struck: Vec<usize>,
     pub selected: usize,
     pub scroll: usize,
+    selected_since: Instant,
+    now: Instant,
+    /// Label width from the last render, for marquee timing
+    label_width: Cell<u32>,
+    /// Back asks to quit, for the root menu
+    quit_on_back: bool,
+    /// Waiting for the answer to the quit confirmation from Back
+    quitting: bool,
 }
 
 impl Menu {
-    pub fn new(title: &'static str, items: Vec<MenuItem>) -> Self {
+    pub fn new(title: impl Into<String>, items: Vec<MenuItem>) -> Self {
+        let now = Instant::now();
         Self {
-            title,
+            title: title.into(),
             items,
+            struck: Vec::new(),
             selected: 0,
             scroll: 0,
+            selected_since: now,
+            now,
+            // Unknown until rendered, zero schedules a tick to find out
+            label_width: Cell::new(0),
+            quit_on_back: false,
+            quitting: false,
         }
     }
 
+    /// Makes Back ask to quit instead of leaving an empty screen, for the
+    /// menu at the bottom of the stack
+    pub fn quit_on_back(mut self) -> Self {
+        self.quit_on_back = true;
+        self
+    }
+
     fn visible_items(&self, height: u32) -> usize {
         // Round up to use available space at bottom
         let available = height as i32 - ITEM_START_Y;
@@ -50,60 +157,95 @@ impl Menu {
 }
 
 impl View for Menu {
-    fn render(&self, display: &mut RenderBuffer) {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
         let bounds = display.bounding_box();
         let width = bounds.size.width;
 
-        // Clear background (below status bar)
-        Rectangle::new(
-            Point::new(0, STATUSBAR_HEIGHT),
-            Size::new(width, bounds.size.height - STATUSBAR_HEIGHT as u32),
-        )
-        .into_styled(PrimitiveStyle::with_fill(BACKGROUND))
-        .draw(display)
-        .unwrap();
-
-        // Draw title
-        let title_style = MonoTextStyle::new(&FONT_10X20, PURPLE);
-        Text::new(self.title, Point::new(10, TITLE_Y), title_style)
-            .draw(display)
-            .unwrap();
-
-        // Draw separator line
-        Rectangle::new(Point::new(0, SEPARATOR_Y), Size::new(width, 2))
-            .into_styled(PrimitiveStyle::with_fill(COMMENT))
-            .draw(display)
-            .unwrap();
+        draw_header(display, theme, &self.title);
 
         // Draw menu items (with scrolling)
-        let item_style = MonoTextStyle::new(&FONT_10X20, FOREGROUND);
-        let selected_style = MonoTextStyle::new(&FONT_10X20, BACKGROUND);
+        let item_style = FontStyle::new(font::regular(), theme.foreground);
+        let selected_style = FontStyle::new(font::regular(), theme.highlight_text);
         let visible = self.visible_items(bounds.size.height);
 
         let has_scroll_up = self.scroll > 0;
         let has_scroll_down = self.scroll + visible < self.items.len();
         let has_arrows = has_scroll_up || has_scroll_down;
         let selection_width = if has_arrows { width - 35 } else { width - 10 };
+        let label_width = selection_width - 10;
+        self.label_width.set(label_width);
 
         for (vi, i) in (self.scroll..(self.scroll + visible).min(self.items.len())).enumerate() {
             let item = &self.items[i];
             let y = ITEM_START_Y + (vi as i32 * ITEM_HEIGHT);
+            // Where the label starts, left of the area while it scrolls
+            let mut x = 10;
 
             if i == self.selected {
                 Rectangle::new(
                     Point::new(5, y - 15),
                     Size::new(selection_width, ITEM_HEIGHT as u32 - 2),
                 )
-                .into_styled(PrimitiveStyle::with_fill(CYAN))
+                .into_styled(PrimitiveStyle::with_fill(theme.highlight))
                 .draw(display)
                 .unwrap();
-                Text::new(item.label, Point::new(10, y), selected_style)
-                    .draw(display)
-                    .unwrap();
+
+                let overflow = font::regular()
+                    .measure(&item.label)
+                    .saturating_sub(label_width);
+                if overflow > 0 {
+                    // Marquee, clipped to the label area
+                    let elapsed = self.now.duration_since(self.selected_since).as_millis();
+                    x -= marquee_offset(elapsed, overflow);
+                    let area = Rectangle::new(
+                        Point::new(10, y - 15),
+                        Size::new(label_width, ITEM_HEIGHT as u32 - 2),
+                    );
+                    Text::new(&item.label, Point::new(x, y), selected_style)
+                        .draw(&mut display.clipped(&area))
+                        .unwrap();
+                } else {
+                    layout::draw_line(
+                        display,
+                        &item.label,
+                        selected_style,
+                        Point::new(10, y),
+                        label_width,
+                        Align::Left,
+                    );
+                }
             } else {
-                Text::new(item.label, Point::new(10, y), item_style)
-                    .draw(display)
-                    .unwrap();
+                layout::draw_line(
+                    display,
+                    &item.label,
+                    item_style,
+                    Point::new(10, y),
+                    label_width,
+                    Align::Left,
+                );
+            }
+
+            if self.struck.contains(&i) {
+                let color = if i == self.selected {
+                    theme.highlight_text
+                } else {
+                    theme.foreground
+                };
+                // Through the text, leaving out leading spaces
+                let indent = item.label.len() - item.label.trim_start().len();
+                let start = x + font::regular().measure(&item.label[..indent]) as i32;
+                let end = x + font::regular().measure(&item.label) as i32;
+                let area = Rectangle::new(
+                    Point::new(10, y - 15),
+                    Size::new(label_width, ITEM_HEIGHT as u32 - 2),
+                );
+                Line::new(
+                    Point::new(start, y - STRIKE_HEIGHT),
+                    Point::new(end, y - STRIKE_HEIGHT),
+                )
+                .into_styled(PrimitiveStyle::with_stroke(color, 1))
+                .draw(&mut display.clipped(&area))
+                .unwrap();
             }
         }
 
@@ -117,7 +259,7 @@ impl View for Menu {
                 Point::new(arrow_x + arrow_size, y + arrow_size),
                 Point::new(arrow_x + arrow_size / 2, y),
             )
-            .into_styled(PrimitiveStyle::with_fill(FOREGROUND))
+            .into_styled(PrimitiveStyle::with_fill(theme.foreground))
             .draw(display)
             .unwrap();
         }
@@ -128,13 +270,65 @@ impl View for Menu {
                 Point::new(arrow_x + arrow_size, y),
                 Point::new(arrow_x + arrow_size / 2, y + arrow_size),
             )
-            .into_styled(PrimitiveStyle::with_fill(FOREGROUND))
+            .into_styled(PrimitiveStyle::with_fill(theme.foreground))
             .draw(display)
             .unwrap();
         }
     }
 
+    fn tick(&mut self, now: Instant) -> Option<Instant> {
+        self.now = now;
+        let label = &self.items.get(self.selected)?.label;
+        let overflow = font::regular()
+            .measure(label)
+            .saturating_sub(self.label_width.get());
+        if overflow == 0 {
+            return None;
+        }
+        let elapsed = now.duration_since(self.selected_since).as_millis();
+        let next = marquee_next(elapsed, overflow) as u64;
+        Some(now + Duration::from_millis(next))
+    }
+
     fn handle(&mut self, action: Action) -> ViewResult {
+        let previous = self.selected;
+        let result = self.navigate(action);
+        if self.selected != previous {
+            // `now` is only as fresh as the last tick, which may be long ago
+            self.now = Instant::now();
+            self.selected_since = self.now;
+        }
+        result
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.items.iter().map(|item| item.label.clone()).collect()
+    }
+
+    fn selected(&self) -> Option<usize> {
+        Some(self.selected)
+    }
+
+    fn on_resume(&mut self, reply: Option<Reply>) -> ViewResult {
+        // Answer to the confirmation pushed by an Exit item or Back
+        let exit = std::mem::take(&mut self.quitting)
+            || matches!(
+                self.items.get(self.selected).map(|item| &item.action),
+                Some(MenuAction::Exit)
+            );
+        match reply.map(Reply::downcast::<bool>) {
+            Some(Ok(true)) if exit => ViewResult::Exit,
+            _ => ViewResult::None,
+        }
+    }
+}
+
+impl Menu {
+    fn navigate(&mut self, action: Action) -> ViewResult {
         match action {
             Action::Up => {
                 if self.selected > 0 {
@@ -146,7 +340,7 @@ impl View for Menu {
                 ViewResult::None
             }
             Action::Down => {
-                if self.selected < self.items.len() - 1 {
+                if self.selected + 1 < self.items.len() {
                     self.selected += 1;
                     // We don't know screen height here, use a reasonable default
                     let visible = 5;
@@ -156,12 +350,37 @@ impl View for Menu {
                 }
                 ViewResult::None
             }
-            Action::Select => match &self.items[self.selected].action {
-                MenuAction::SubMenu(create_menu) => ViewResult::Push(Box::new(create_menu())),
-                MenuAction::Exit => ViewResult::Exit,
-                MenuAction::None => ViewResult::None,
-            },
+            Action::Select => {
+                let Some(item) = self.items.get(self.selected) else {
+                    return ViewResult::None;
+                };
+                match &item.action {
+                    MenuAction::SubMenu(create_menu) => ViewResult::Push(Box::new(create_menu())),
+                    MenuAction::Open(create_view) => ViewResult::Push(create_view()),
+                    MenuAction::Run(run) => {
+                        run();
+                        ViewResult::None
+                    }
+                    MenuAction::Build(build) => ViewResult::Push(build()),
+                    MenuAction::Shell(command) => {
+                        shell::run_detached(&item.label, command);
+                        ViewResult::None
+                    }
+                    MenuAction::Output { command, timeout } => ViewResult::Push(Box::new(
+                        CommandView::new(item.label.clone(), command, *timeout),
+                    )),
+                    MenuAction::Exit => {
+                        ViewResult::Push(Box::new(Confirm::new("Exit", "Quit OpenPager?")))
+                    }
+                    MenuAction::None => ViewResult::None,
+                }
+            }
+            Action::Back if self.quit_on_back => {
+                self.quitting = true;
+                ViewResult::Push(Box::new(Confirm::new("Exit", "Quit OpenPager?")))
+            }
             Action::Back => ViewResult::Pop,
+            Action::Power | Action::Screenshot => ViewResult::None,
         }
     }
 }
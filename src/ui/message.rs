@@ -0,0 +1,102 @@
+use embedded_graphics::prelude::*;
+
+use super::font::{self, FontStyle};
+use super::layout::{self, Align};
+use super::menu::{ITEM_START_Y, draw_header};
+use super::{Theme, View, ViewResult};
+use crate::inbox::Message;
+use crate::platform::clock::LocalTime;
+use crate::platform::{Action, Priority, RenderBuffer};
+
+/// Full text of one message, scrolled line by line
+pub struct MessageView {
+    message: Message,
+    scroll: usize,
+}
+
+impl MessageView {
+    pub fn new(message: Message) -> Self {
+        Self { message, scroll: 0 }
+    }
+
+    /// Wrapped subject, then when it arrived and was resolved, each line
+    /// with whether it is one of those details
+    fn lines(&self, width: u32) -> Vec<(String, bool)> {
+        let message = &self.message;
+        let mut details = vec![match message.count {
+            1 => format!("Received at {}", time(message.last_seen)),
+            count => format!("Seen {} times, last at {}", count, time(message.last_seen)),
+        }];
+        if let Some(resolved) = message.resolved {
+            details.push(format!("Resolved at {}", time(resolved)));
+        }
+        let subject = layout::wrap(font::regular(), &message.subject, width - 20);
+        subject
+            .into_iter()
+            .map(|line| (line, false))
+            .chain(std::iter::once((String::new(), true)))
+            .chain(details.into_iter().map(|line| (line, true)))
+            .collect()
+    }
+}
+
+fn time(time: LocalTime) -> String {
+    format!("{:02}:{:02}", time.hour, time.minute)
+}
+
+impl View for MessageView {
+    fn title(&self) -> &str {
+        match self.message.priority {
+            Priority::Low => "Low priority",
+            Priority::Normal => "Message",
+            Priority::High => "High priority",
+            Priority::Critical => "CRITICAL",
+        }
+    }
+
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let bounds = display.bounding_box();
+        let width = bounds.size.width;
+
+        draw_header(display, theme, self.title());
+
+        let color = match self.message.priority {
+            Priority::Critical => theme.danger,
+            _ => theme.foreground,
+        };
+        let style = FontStyle::new(font::regular(), color);
+        let detail_style = FontStyle::new(font::regular(), theme.muted);
+        let line_height = font::regular().line_height() as i32;
+        let mut y = ITEM_START_Y;
+        for (line, detail) in self.lines(width).iter().skip(self.scroll) {
+            if y - line_height >= bounds.size.height as i32 {
+                break;
+            }
+            layout::draw_line(
+                display,
+                line,
+                if *detail { detail_style } else { style },
+                Point::new(10, y),
+                width - 20,
+                Align::Left,
+            );
+            y += line_height;
+        }
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        match action {
+            Action::Up => self.scroll = self.scroll.saturating_sub(1),
+            Action::Down => {
+                // Width is only known while rendering, assume the display's
+                let lines = self.lines(crate::DISPLAY_WIDTH).len();
+                if self.scroll + 1 < lines {
+                    self.scroll += 1;
+                }
+            }
+            Action::Select | Action::Power | Action::Screenshot => {}
+            Action::Back => return ViewResult::Pop,
+        }
+        ViewResult::None
+    }
+}
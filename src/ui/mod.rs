@@ -1,12 +1,35 @@
 //! UI components
 
-pub mod colors;
+pub mod clockface;
+mod command;
+pub mod dialog;
+pub mod external;
+pub mod font;
 mod icons;
+mod inbox;
+pub mod launcher;
+pub mod layout;
+pub mod lock;
 mod menu;
+mod message;
+mod segments;
+mod settings;
+mod stack;
 mod statusbar;
+mod text;
+pub mod theme;
+pub mod toast;
 
+pub use icons::Icon;
+pub use inbox::InboxView;
 pub use menu::{Menu, MenuAction, MenuItem};
+pub use settings::SettingsView;
+pub use stack::ViewStack;
 pub use statusbar::{STATUSBAR_HEIGHT, StatusBar};
+pub use theme::Theme;
+
+use std::any::Any;
+use std::time::Instant;
 
 use crate::platform::{Action, RenderBuffer};
 
@@ -14,10 +37,64 @@ pub enum ViewResult {
     None,
     Push(Box<dyn View>),
     Pop,
+    /// Pops and hands the reply to the parent's `on_resume`
+    PopWith(Reply),
     Exit,
 }
 
+/// Value a child view returns to its parent, for pickers, dialogs and forms
+pub struct Reply(Box<dyn Any>);
+
+impl Reply {
+    pub fn new<T: Any>(value: T) -> Self {
+        Self(Box::new(value))
+    }
+
+    /// The value if it is a `T`, otherwise the reply back
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        self.0.downcast().map(|value| *value).map_err(Self)
+    }
+}
+
 pub trait View {
-    fn render(&self, buffer: &mut RenderBuffer);
+    fn render(&self, buffer: &mut RenderBuffer, theme: &Theme);
     fn handle(&mut self, action: Action) -> ViewResult;
+
+    /// Called when pushed onto the stack
+    fn on_enter(&mut self) {}
+
+    /// Called right before being popped off the stack
+    fn on_exit(&mut self) {}
+
+    /// Called when the view above was popped, with its reply if it gave one
+    fn on_resume(&mut self, _reply: Option<Reply>) -> ViewResult {
+        ViewResult::None
+    }
+
+    /// Overlays are drawn on top of the view beneath them instead of
+    /// replacing it, like dialogs
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// Name listed by the control socket
+    fn title(&self) -> &str {
+        ""
+    }
+
+    /// Rows Up and Down move through, for opening items by name
+    fn labels(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Index into `labels` of the highlighted row
+    fn selected(&self) -> Option<usize> {
+        None
+    }
+
+    /// Called before every `render`, returns when the view next needs a
+    /// frame on its own, `None` sleeps until input arrives
+    fn tick(&mut self, _now: Instant) -> Option<Instant> {
+        None
+    }
 }
@@ -0,0 +1,91 @@
+//! Seven-segment digits for clocks too large for the bitmap fonts
+
+use embedded_graphics::{
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+use crate::platform::RenderBuffer;
+
+/// Lit segments per digit, bit 0 is the top segment going clockwise, bit 6
+/// the middle one
+const DIGITS: [u8; 10] = [0x3F, 0x06, 0x5B, 0x4F, 0x66, 0x6D, 0x7D, 0x07, 0x7F, 0x6F];
+
+fn thickness(height: u32) -> u32 {
+    (height / 10).max(2)
+}
+
+fn advance(c: char, height: u32) -> u32 {
+    let t = thickness(height);
+    match c {
+        ':' => 2 * t,
+        _ => height / 2 + t,
+    }
+}
+
+/// Width of `text` drawn `height` pixels tall, without trailing spacing
+pub fn measure(text: &str, height: u32) -> u32 {
+    let total: u32 = text.chars().map(|c| advance(c, height)).sum();
+    total.saturating_sub(thickness(height))
+}
+
+/// Draws digits and colons with `top_left` as the corner of the first one,
+/// other characters leave a blank
+pub fn draw(display: &mut RenderBuffer, text: &str, top_left: Point, height: u32, color: Rgb888) {
+    let t = thickness(height) as i32;
+    let h = height as i32;
+    let w = h / 2;
+    let half = h / 2;
+    let style = PrimitiveStyle::with_fill(color);
+    let mut x = top_left.x;
+    let y = top_left.y;
+
+    for c in text.chars() {
+        let mut fill = |left: i32, top: i32, width: i32, height: i32| {
+            Rectangle::new(
+                Point::new(left, top),
+                Size::new(width.max(0) as u32, height.max(0) as u32),
+            )
+            .into_styled(style)
+            .draw(display)
+            .unwrap();
+        };
+        match c {
+            ':' => {
+                fill(x, y + h / 3 - t / 2, t, t);
+                fill(x, y + 2 * h / 3 - t / 2, t, t);
+            }
+            '0'..='9' => {
+                let segments = DIGITS[c as usize - '0' as usize];
+                let upper = half - t / 2 - t;
+                let lower_top = y + half - t / 2 + t;
+                let lower = y + h - t - lower_top;
+                let lit = |bit: u8| segments & (1 << bit) != 0;
+                if lit(0) {
+                    fill(x + t, y, w - 2 * t, t);
+                }
+                if lit(1) {
+                    fill(x + w - t, y + t, t, upper);
+                }
+                if lit(2) {
+                    fill(x + w - t, lower_top, t, lower);
+                }
+                if lit(3) {
+                    fill(x + t, y + h - t, w - 2 * t, t);
+                }
+                if lit(4) {
+                    fill(x, lower_top, t, lower);
+                }
+                if lit(5) {
+                    fill(x, y + t, t, upper);
+                }
+                if lit(6) {
+                    fill(x + t, y + half - t / 2, w - 2 * t, t);
+                }
+            }
+            _ => {}
+        }
+        x += advance(c, height) as i32;
+    }
+}
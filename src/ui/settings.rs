@@ -0,0 +1,303 @@
+use embedded_graphics::{
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+use super::dialog::Choice;
+use super::font::{self, FontStyle};
+use super::layout::{self, Align};
+use super::lock::SetCode;
+use super::menu::{ITEM_HEIGHT, ITEM_START_Y, draw_header};
+use super::toast;
+use super::{Reply, Theme, View, ViewResult};
+use crate::platform::{Action, RenderBuffer};
+use crate::settings::{self, Kind, SETTINGS, Setting, Value};
+
+const SLIDER_WIDTH: u32 = 100;
+const TEXT_CHARSET: &str = " abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-_.";
+
+/// Lists every setting under `section.` with toggles, sliders and choices,
+/// changes are applied and saved as they are made
+pub struct SettingsView {
+    title: String,
+    settings: Vec<&'static Setting>,
+    selected: usize,
+    scroll: usize,
+    editing: bool,
+    cursor: usize,
+    /// Text being edited, saved once editing ends
+    draft: Vec<char>,
+    /// Values when the view was opened, to tell if anything changed
+    initial: Vec<Value>,
+}
+
+impl SettingsView {
+    pub fn new(title: impl Into<String>, section: &str) -> Self {
+        let prefix = format!("{}.", section);
+        Self {
+            title: title.into(),
+            settings: SETTINGS
+                .iter()
+                .filter(|s| s.key.starts_with(&prefix))
+                .collect(),
+            selected: 0,
+            scroll: 0,
+            editing: false,
+            cursor: 0,
+            draft: Vec::new(),
+            initial: Vec::new(),
+        }
+    }
+
+    fn visible_items(height: u32) -> usize {
+        ((height as i32 - ITEM_START_Y + ITEM_HEIGHT - 1) / ITEM_HEIGHT).max(1) as usize
+    }
+
+    /// Up/Down while editing, `delta` is +1 for Up
+    fn adjust(&mut self, delta: i32) {
+        let setting = self.settings[self.selected];
+        let value = match (&setting.kind, settings::get(setting.key)) {
+            (Kind::Range { min, max, step }, Value::Int(v)) => {
+                Value::Int((v + delta * step).clamp(*min, *max))
+            }
+            (Kind::Text { .. }, _) => {
+                if self.cursor >= self.draft.len() {
+                    self.draft.resize(self.cursor + 1, ' ');
+                }
+                let charset: Vec<char> = TEXT_CHARSET.chars().collect();
+                let pos = charset
+                    .iter()
+                    .position(|c| *c == self.draft[self.cursor])
+                    .unwrap_or(0) as i32;
+                self.draft[self.cursor] =
+                    charset[(pos + delta).rem_euclid(charset.len() as i32) as usize];
+                return;
+            }
+            _ => return,
+        };
+        settings::set(setting.key, value);
+    }
+
+    /// Saves the draft, the only point a text setting is written
+    fn finish_text(&mut self) {
+        let setting = self.settings[self.selected];
+        let text: String = self.draft.drain(..).collect();
+        settings::set(setting.key, Value::Text(text.trim().to_string()));
+    }
+
+    fn current_values(&self) -> Vec<Value> {
+        self.settings.iter().map(|s| settings::get(s.key)).collect()
+    }
+
+    fn value_label(&self, setting: &Setting, selected: bool) -> String {
+        match (&setting.kind, settings::get(setting.key)) {
+            (_, Value::Bool(b)) => if b { "[on]" } else { "[off]" }.to_string(),
+            (_, Value::Int(v)) => v.to_string(),
+            (Kind::Choice(options), Value::Choice(i)) => options[i].to_string(),
+            (Kind::Code { .. }, Value::Text(code)) => {
+                if code.is_empty() { "none" } else { "set" }.to_string()
+            }
+            (_, Value::Choice(i)) => i.to_string(),
+            (_, Value::Text(_)) if self.editing && selected => {
+                // Show the cursor position with brackets
+                let mut chars = self.draft.clone();
+                if self.cursor >= chars.len() {
+                    chars.resize(self.cursor + 1, ' ');
+                }
+                let before: String = chars[..self.cursor].iter().collect();
+                let after: String = chars[self.cursor + 1..].iter().collect();
+                format!("{}[{}]{}", before, chars[self.cursor], after)
+            }
+            (_, Value::Text(text)) => text,
+        }
+    }
+}
+
+impl View for SettingsView {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let bounds = display.bounding_box();
+        let width = bounds.size.width;
+
+        draw_header(display, theme, &self.title);
+
+        let visible = Self::visible_items(bounds.size.height);
+        let end = (self.scroll + visible).min(self.settings.len());
+        for (vi, i) in (self.scroll..end).enumerate() {
+            let setting = self.settings[i];
+            let y = ITEM_START_Y + (vi as i32 * ITEM_HEIGHT);
+            let selected = i == self.selected;
+
+            let color = if selected {
+                let highlight = if self.editing {
+                    theme.editing
+                } else {
+                    theme.highlight
+                };
+                Rectangle::new(
+                    Point::new(5, y - 15),
+                    Size::new(width - 10, ITEM_HEIGHT as u32 - 2),
+                )
+                .into_styled(PrimitiveStyle::with_fill(highlight))
+                .draw(display)
+                .unwrap();
+                theme.highlight_text
+            } else {
+                theme.foreground
+            };
+            let style = FontStyle::new(font::regular(), color);
+
+            // Value on the right, at most half the row, label gets the rest
+            let right = width as i32 - 10;
+            let value = self.value_label(setting, selected);
+            let value_width = font::regular().measure(&value).min((width - 20) / 2);
+            let mut value_left = right - value_width as i32;
+            layout::draw_line(
+                display,
+                &value,
+                style,
+                Point::new(value_left, y),
+                value_width,
+                Align::Right,
+            );
+
+            // Slider to the left of the number
+            if let (Kind::Range { min, max, .. }, Value::Int(v)) =
+                (&setting.kind, settings::get(setting.key))
+            {
+                let x = right - 40 - SLIDER_WIDTH as i32;
+                value_left = value_left.min(x);
+                Rectangle::new(Point::new(x, y - 10), Size::new(SLIDER_WIDTH, 8))
+                    .into_styled(PrimitiveStyle::with_stroke(color, 1))
+                    .draw(display)
+                    .unwrap();
+                let filled = (SLIDER_WIDTH as i32 * (v - min) / (max - min).max(1)) as u32;
+                Rectangle::new(Point::new(x, y - 10), Size::new(filled, 8))
+                    .into_styled(PrimitiveStyle::with_fill(color))
+                    .draw(display)
+                    .unwrap();
+            }
+
+            let label_width = (value_left - 20).max(0) as u32;
+            layout::draw_line(
+                display,
+                setting.label,
+                style,
+                Point::new(10, y),
+                label_width,
+                Align::Left,
+            );
+        }
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        if self.settings.is_empty() {
+            return match action {
+                Action::Back => ViewResult::Pop,
+                _ => ViewResult::None,
+            };
+        }
+
+        if self.editing {
+            let is_text = matches!(self.settings[self.selected].kind, Kind::Text { .. });
+            match action {
+                Action::Up => self.adjust(1),
+                Action::Down => self.adjust(-1),
+                Action::Select if is_text => {
+                    // One past the end appends, no further
+                    if let Kind::Text { max_len } = self.settings[self.selected].kind {
+                        self.cursor = (self.cursor + 1).min(max_len - 1).min(self.draft.len());
+                    }
+                }
+                Action::Select | Action::Back => {
+                    if is_text {
+                        self.finish_text();
+                    }
+                    self.editing = false;
+                }
+                Action::Power | Action::Screenshot => {}
+            }
+            return ViewResult::None;
+        }
+
+        match action {
+            Action::Up => {
+                if self.selected > 0 {
+                    self.selected -= 1;
+                    self.scroll = self.scroll.min(self.selected);
+                }
+            }
+            Action::Down => {
+                if self.selected + 1 < self.settings.len() {
+                    self.selected += 1;
+                    // Same assumption about screen height as the menu
+                    let visible = 5;
+                    if self.selected >= self.scroll + visible {
+                        self.scroll = self.selected - visible + 1;
+                    }
+                }
+            }
+            Action::Select => {
+                let setting = self.settings[self.selected];
+                match (&setting.kind, settings::get(setting.key)) {
+                    (_, Value::Bool(b)) => settings::set(setting.key, Value::Bool(!b)),
+                    (Kind::Choice(options), Value::Choice(i)) => {
+                        return ViewResult::Push(Box::new(Choice::new(setting.label, options, i)));
+                    }
+                    (Kind::Code { .. }, _) => {
+                        return ViewResult::Push(Box::new(SetCode::new(setting.label)));
+                    }
+                    (_, value) => {
+                        if let Value::Text(text) = value {
+                            self.draft = text.chars().collect();
+                        }
+                        self.editing = true;
+                        self.cursor = 0;
+                    }
+                }
+            }
+            Action::Back => return ViewResult::Pop,
+            Action::Power | Action::Screenshot => {}
+        }
+        ViewResult::None
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.settings.iter().map(|s| s.label.to_string()).collect()
+    }
+
+    fn selected(&self) -> Option<usize> {
+        Some(self.selected)
+    }
+
+    fn on_resume(&mut self, reply: Option<Reply>) -> ViewResult {
+        // Answer from the choice or code dialog pushed by Select
+        let setting = self.settings[self.selected];
+        match reply.map(Reply::downcast::<usize>) {
+            Some(Ok(index)) => settings::set(setting.key, Value::Choice(index)),
+            Some(Err(reply)) => {
+                if let Ok(code) = reply.downcast::<String>() {
+                    settings::set(setting.key, Value::Text(code));
+                }
+            }
+            None => {}
+        }
+        ViewResult::None
+    }
+
+    fn on_enter(&mut self) {
+        self.initial = self.current_values();
+    }
+
+    fn on_exit(&mut self) {
+        if self.editing && matches!(self.settings[self.selected].kind, Kind::Text { .. }) {
+            self.finish_text();
+        }
+        if self.current_values() != self.initial {
+            toast::show("Settings saved");
+        }
+    }
+}
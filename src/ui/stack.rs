@@ -0,0 +1,78 @@
+use super::{Reply, Theme, View, ViewResult};
+use crate::platform::RenderBuffer;
+
+/// Navigation stack, the last view is on screen and receives input
+pub struct ViewStack {
+    views: Vec<Box<dyn View>>,
+}
+
+impl ViewStack {
+    pub fn new(mut root: Box<dyn View>) -> Self {
+        root.on_enter();
+        Self { views: vec![root] }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.views.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.views.len()
+    }
+
+    /// Views from the root up to the one on screen
+    pub fn iter(&self) -> impl Iterator<Item = &dyn View> {
+        self.views.iter().map(|view| view.as_ref())
+    }
+
+    pub fn top_mut(&mut self) -> Option<&mut Box<dyn View>> {
+        self.views.last_mut()
+    }
+
+    /// Draws the top view, and when it is an overlay the views beneath it
+    /// down to the first one that fills the screen
+    pub fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let base = self
+            .views
+            .iter()
+            .rposition(|view| !view.is_overlay())
+            .unwrap_or(0);
+        for view in self.views.iter().skip(base) {
+            view.render(display, theme);
+        }
+    }
+
+    /// Applies a view's result, including whatever the parent answers from
+    /// `on_resume`, returns false once the application should exit
+    pub fn apply(&mut self, mut result: ViewResult) -> bool {
+        loop {
+            result = match result {
+                ViewResult::None => return true,
+                ViewResult::Push(mut view) => {
+                    view.on_enter();
+                    self.views.push(view);
+                    return true;
+                }
+                ViewResult::Pop => self.pop(None),
+                ViewResult::PopWith(reply) => self.pop(Some(reply)),
+                ViewResult::Exit => {
+                    // Every view gets its `on_exit`, top first
+                    while let Some(mut view) = self.views.pop() {
+                        view.on_exit();
+                    }
+                    return false;
+                }
+            };
+        }
+    }
+
+    fn pop(&mut self, reply: Option<Reply>) -> ViewResult {
+        if let Some(mut view) = self.views.pop() {
+            view.on_exit();
+        }
+        match self.views.last_mut() {
+            Some(parent) => parent.on_resume(reply),
+            None => ViewResult::None,
+        }
+    }
+}
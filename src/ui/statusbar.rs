@@ -1,12 +1,18 @@
 use embedded_graphics::{
-    mono_font::{ascii::FONT_9X15_BOLD, MonoTextStyle},
     prelude::*,
     primitives::{PrimitiveStyle, Rectangle},
 };
 
-use super::colors::*;
+use super::Theme;
+use super::font::{self, FontStyle};
 use super::icons;
-use crate::platform::{read_battery, RenderBuffer};
+use super::layout::{self, Align};
+use crate::dnd;
+use crate::mqtt;
+use crate::platform::{clock, read_battery, RenderBuffer};
+use crate::settings;
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 pub const STATUSBAR_HEIGHT: i32 = 24;
 
@@ -19,58 +25,77 @@ impl Default for StatusBar {
 }
 
 impl StatusBar {
-    fn read_time() -> (u8, u8) {
-        use std::time::{SystemTime, UNIX_EPOCH};
+    pub(super) fn read_time() -> (u8, u8) {
+        let now = clock::local_time();
+        (now.hour, now.minute)
+    }
+
+    /// The clock changes on the minute, battery and quiet hours follow along
+    pub fn next_wakeup(&self, now: Instant) -> Instant {
         let secs = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map(|d| d.as_secs())
             .unwrap_or(0);
-        // Convert to hours:minutes (UTC, add timezone offset if needed)
-        let secs_today = secs % 86400;
-        let hours = ((secs_today / 3600) as u8) % 24;
-        let minutes = ((secs_today % 3600) / 60) as u8;
-        (hours, minutes)
+        now + Duration::from_secs(60 - secs % 60)
     }
 }
 
 const ICON_SPACING: i32 = 6;
 
 impl StatusBar {
-    pub fn render(&self, display: &mut RenderBuffer) {
+    pub fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
         let bounds = display.bounding_box();
         let width = bounds.size.width;
 
         // Status bar background
         Rectangle::new(Point::zero(), Size::new(width, STATUSBAR_HEIGHT as u32))
-            .into_styled(PrimitiveStyle::with_fill(SELECTION))
+            .into_styled(PrimitiveStyle::with_fill(theme.statusbar))
             .draw(display)
             .unwrap();
 
-        let text_style = MonoTextStyle::new(&FONT_9X15_BOLD, FOREGROUND);
-
-        // Left: Title
-        embedded_graphics::text::Text::new("OpenPager", Point::new(6, 18), text_style)
-            .draw(display)
-            .unwrap();
+        let text_style = FontStyle::new(font::small(), theme.statusbar_text);
 
         // Right side: draw icons from right to left, tracking cursor
         let mut cursor = width as i32 - 6;
 
         // Battery icon
         let (battery, charging) = read_battery();
-        let battery_width = icons::battery::draw(display, cursor, battery, charging);
+        let battery_width = icons::battery::draw(display, theme, cursor, battery, charging);
         cursor -= battery_width + ICON_SPACING;
 
-        // Clock (before icons) - 5 chars * 9px = 45px wide
+        // Do-not-disturb, dimmed while waiting for quiet hours
+        if dnd::mode() != dnd::Mode::Off {
+            let dnd_width = icons::dnd::draw(display, theme, cursor, dnd::active());
+            cursor -= dnd_width + ICON_SPACING;
+        }
+
+        // MQTT connection, dimmed while it is down
+        let link = mqtt::state();
+        if link != mqtt::State::Off {
+            let connected = link == mqtt::State::Connected;
+            let link_width = icons::link::draw(display, theme, cursor, connected);
+            cursor -= link_width + ICON_SPACING;
+        }
+
+        // Clock (before icons)
         let (hours, minutes) = Self::read_time();
         let time_str = format!("{:02}:{:02}", hours, minutes);
-        let clock_width = 45;
-        embedded_graphics::text::Text::new(
-            &time_str,
-            Point::new(cursor - clock_width, 18),
+        let clock_width = font::small().measure(&time_str) as i32;
+        cursor -= clock_width;
+        embedded_graphics::text::Text::new(&time_str, Point::new(cursor, 18), text_style)
+            .draw(display)
+            .unwrap();
+
+        // Left: Device name, in whatever space the right side left over
+        let name = settings::get_text("display.name");
+        let name_width = (cursor - ICON_SPACING - 6).max(0) as u32;
+        layout::draw_line(
+            display,
+            &name,
             text_style,
-        )
-        .draw(display)
-        .unwrap();
+            Point::new(6, 18),
+            name_width,
+            Align::Left,
+        );
     }
 }
@@ -0,0 +1,112 @@
+use std::cell::Cell;
+
+use embedded_graphics::prelude::*;
+
+use super::font::{self, FontStyle};
+use super::layout::{self, Align};
+use super::menu::{ITEM_START_Y, draw_header};
+use super::{Theme, View, ViewResult};
+use crate::platform::{Action, RenderBuffer};
+
+/// Titled block of plain text such as command output, in the small font and
+/// scrolled line by line
+pub struct TextView {
+    title: String,
+    lines: Vec<String>,
+    scroll: usize,
+    /// Pinned to the last line as text is added, until scrolled up
+    follow: bool,
+    /// Lines that fit, from the last render
+    visible: Cell<usize>,
+}
+
+impl TextView {
+    pub fn new(title: impl Into<String>, text: &str) -> Self {
+        let mut view = Self {
+            title: title.into(),
+            lines: Vec::new(),
+            scroll: 0,
+            follow: false,
+            visible: Cell::new(1),
+        };
+        view.set_text(text);
+        view
+    }
+
+    /// Starts at the end and stays there as `set_text` adds to it
+    pub fn following(mut self) -> Self {
+        self.follow = true;
+        self
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        // Width is only known while rendering, wrap for the display's
+        self.lines = layout::wrap(font::small(), text, crate::DISPLAY_WIDTH - 20);
+        if self.lines.last().is_some_and(String::is_empty) {
+            self.lines.pop();
+        }
+    }
+
+    /// First line shown
+    fn top(&self) -> usize {
+        let last_page = self.lines.len().saturating_sub(self.visible.get());
+        if self.follow {
+            last_page
+        } else {
+            self.scroll.min(last_page)
+        }
+    }
+}
+
+impl View for TextView {
+    fn render(&self, display: &mut RenderBuffer, theme: &Theme) {
+        let bounds = display.bounding_box();
+        let width = bounds.size.width;
+
+        draw_header(display, theme, &self.title);
+
+        let style = FontStyle::new(font::small(), theme.foreground);
+        let line_height = font::small().line_height() as i32;
+        // Lines whose baseline is on screen
+        let available = bounds.size.height as i32 - ITEM_START_Y;
+        self.visible
+            .set((available / line_height + 1).max(1) as usize);
+        let mut y = ITEM_START_Y;
+        for line in self.lines.iter().skip(self.top()) {
+            if y - line_height >= bounds.size.height as i32 {
+                break;
+            }
+            layout::draw_line(
+                display,
+                line,
+                style,
+                Point::new(10, y),
+                width - 20,
+                Align::Left,
+            );
+            y += line_height;
+        }
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn handle(&mut self, action: Action) -> ViewResult {
+        match action {
+            Action::Up => {
+                self.scroll = self.top().saturating_sub(1);
+                self.follow = false;
+            }
+            Action::Down => {
+                let last_page = self.lines.len().saturating_sub(self.visible.get());
+                self.scroll = (self.top() + 1).min(last_page);
+                // Scrolling back to the end follows again
+                self.follow = self.scroll == last_page;
+            }
+            Action::Select | Action::Power | Action::Screenshot => {}
+            Action::Back => return ViewResult::Pop,
+        }
+        ViewResult::None
+    }
+}
@@ -0,0 +1,174 @@
+//! Color themes, selected by the `display.theme` setting
+
+use std::sync::Mutex;
+
+use embedded_graphics::pixelcolor::Rgb888;
+
+use crate::config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub background: Rgb888,
+    pub foreground: Rgb888,
+    pub statusbar: Rgb888,
+    pub statusbar_text: Rgb888,
+    pub title: Rgb888,
+    pub separator: Rgb888,
+    pub muted: Rgb888,
+    pub highlight: Rgb888,
+    pub highlight_text: Rgb888,
+    pub editing: Rgb888,
+    pub danger: Rgb888,
+    pub warning: Rgb888,
+    pub success: Rgb888,
+    pub info: Rgb888,
+}
+
+/// File read for the `custom` theme, `base = <builtin>` picks the starting point
+const CUSTOM_FILE: &str = "theme.conf";
+
+impl Theme {
+    /// Dracula
+    pub const fn dark() -> Self {
+        Self {
+            background: Rgb888::new(0x28, 0x2A, 0x36),
+            foreground: Rgb888::new(0xF8, 0xF8, 0xF2),
+            statusbar: Rgb888::new(0x44, 0x47, 0x5A),
+            statusbar_text: Rgb888::new(0xF8, 0xF8, 0xF2),
+            title: Rgb888::new(0xBD, 0x93, 0xF9),
+            separator: Rgb888::new(0x62, 0x72, 0xA4),
+            muted: Rgb888::new(0x62, 0x72, 0xA4),
+            highlight: Rgb888::new(0x8B, 0xE9, 0xFD),
+            highlight_text: Rgb888::new(0x28, 0x2A, 0x36),
+            editing: Rgb888::new(0xFF, 0xB8, 0x6C),
+            danger: Rgb888::new(0xFF, 0x55, 0x55),
+            warning: Rgb888::new(0xF1, 0xFA, 0x8C),
+            success: Rgb888::new(0x50, 0xFA, 0x7B),
+            info: Rgb888::new(0x8B, 0xE9, 0xFD),
+        }
+    }
+
+    pub const fn light() -> Self {
+        Self {
+            background: Rgb888::new(0xFA, 0xFA, 0xFA),
+            foreground: Rgb888::new(0x20, 0x20, 0x20),
+            statusbar: Rgb888::new(0xD4, 0xD4, 0xDC),
+            statusbar_text: Rgb888::new(0x20, 0x20, 0x20),
+            title: Rgb888::new(0x5A, 0x2D, 0xB0),
+            separator: Rgb888::new(0x90, 0x90, 0xA0),
+            muted: Rgb888::new(0x70, 0x70, 0x80),
+            highlight: Rgb888::new(0x00, 0x66, 0x99),
+            highlight_text: Rgb888::new(0xFF, 0xFF, 0xFF),
+            editing: Rgb888::new(0xC0, 0x5A, 0x00),
+            danger: Rgb888::new(0xC6, 0x28, 0x28),
+            warning: Rgb888::new(0xB5, 0x89, 0x00),
+            success: Rgb888::new(0x2E, 0x7D, 0x32),
+            info: Rgb888::new(0x00, 0x66, 0x99),
+        }
+    }
+
+    /// Pure black and white with saturated accents, for direct sunlight
+    pub const fn high_contrast() -> Self {
+        Self {
+            background: Rgb888::new(0x00, 0x00, 0x00),
+            foreground: Rgb888::new(0xFF, 0xFF, 0xFF),
+            statusbar: Rgb888::new(0xFF, 0xFF, 0xFF),
+            statusbar_text: Rgb888::new(0x00, 0x00, 0x00),
+            title: Rgb888::new(0xFF, 0xFF, 0x00),
+            separator: Rgb888::new(0xFF, 0xFF, 0xFF),
+            muted: Rgb888::new(0xC0, 0xC0, 0xC0),
+            highlight: Rgb888::new(0xFF, 0xFF, 0x00),
+            highlight_text: Rgb888::new(0x00, 0x00, 0x00),
+            editing: Rgb888::new(0x00, 0xFF, 0xFF),
+            danger: Rgb888::new(0xFF, 0x00, 0x00),
+            warning: Rgb888::new(0xFF, 0xFF, 0x00),
+            success: Rgb888::new(0x00, 0xFF, 0x00),
+            info: Rgb888::new(0x00, 0xFF, 0xFF),
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// Colour by its name in theme files, such as `danger`
+    pub fn named(&self, name: &str) -> Option<Rgb888> {
+        let mut theme = *self;
+        theme.slot(name).copied()
+    }
+
+    fn slot(&mut self, name: &str) -> Option<&mut Rgb888> {
+        Some(match name {
+            "background" => &mut self.background,
+            "foreground" => &mut self.foreground,
+            "statusbar" => &mut self.statusbar,
+            "statusbar_text" => &mut self.statusbar_text,
+            "title" => &mut self.title,
+            "separator" => &mut self.separator,
+            "muted" => &mut self.muted,
+            "highlight" => &mut self.highlight,
+            "highlight_text" => &mut self.highlight_text,
+            "editing" => &mut self.editing,
+            "danger" => &mut self.danger,
+            "warning" => &mut self.warning,
+            "success" => &mut self.success,
+            "info" => &mut self.info,
+            _ => return None,
+        })
+    }
+
+    /// Reads `key = #RRGGBB` lines on top of the `base` theme
+    pub fn parse(text: &str) -> Self {
+        let entries = config::parse(text);
+        let mut theme = entries
+            .iter()
+            .find(|(key, _)| key == "base")
+            .and_then(|(_, base)| Self::builtin(base))
+            .unwrap_or(Self::dark());
+        for (key, value) in &entries {
+            let Some(color) = parse_color(value) else {
+                continue;
+            };
+            let Some(slot) = theme.slot(key) else {
+                continue;
+            };
+            *slot = color;
+        }
+        theme
+    }
+}
+
+pub(super) fn parse_color(value: &str) -> Option<Rgb888> {
+    let hex = value.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let rgb = u32::from_str_radix(hex, 16).ok()?;
+    Some(Rgb888::new((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+static ACTIVE: Mutex<Theme> = Mutex::new(Theme::dark());
+
+/// Theme to render the next frame with
+pub fn active() -> Theme {
+    *ACTIVE.lock().unwrap()
+}
+
+/// Switches to a built-in theme, or `custom` from `theme.conf`
+pub fn select(name: &str) {
+    let theme = Theme::builtin(name).unwrap_or_else(|| {
+        match std::fs::read_to_string(config::path(CUSTOM_FILE)) {
+            Ok(text) => Theme::parse(&text),
+            Err(e) => {
+                eprintln!("Error loading theme {}: {}", CUSTOM_FILE, e);
+                Theme::dark()
+            }
+        }
+    });
+    *ACTIVE.lock().unwrap() = theme;
+}
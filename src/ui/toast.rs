@@ -0,0 +1,110 @@
+//! Short one-line notices shown above whatever view is open, then slid
+//! off the bottom of the screen
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle, RoundedRectangle},
+};
+
+use super::Theme;
+use super::font::{self, FontStyle};
+use super::layout::{self, Align};
+use crate::platform::{self, RenderBuffer};
+
+const SHOW: Duration = Duration::from_millis(2500);
+const SLIDE: Duration = Duration::from_millis(300);
+const SLIDE_FRAME: Duration = Duration::from_millis(30);
+const HEIGHT: u32 = 28;
+const MARGIN: i32 = 8;
+const PADDING: u32 = 12;
+
+struct Toast {
+    text: String,
+    since: Instant,
+}
+
+struct State {
+    queue: VecDeque<String>,
+    current: Option<Toast>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    queue: VecDeque::new(),
+    current: None,
+});
+
+/// Queues `text` to be shown after the toasts already waiting, posting
+/// the one on screen again just keeps it up longer
+pub fn show(text: impl Into<String>) {
+    let text = text.into();
+    let state = &mut *STATE.lock().unwrap();
+    match &mut state.current {
+        Some(toast) if toast.text == text && state.queue.is_empty() => {
+            toast.since = Instant::now();
+        }
+        _ if state.queue.back() == Some(&text) => {}
+        _ => state.queue.push_back(text),
+    }
+    platform::wake();
+}
+
+/// Moves on to the next toast once the current one is gone, returns when
+/// the layer next needs a frame
+pub fn tick(now: Instant) -> Option<Instant> {
+    let mut state = STATE.lock().unwrap();
+    if state
+        .current
+        .as_ref()
+        .is_some_and(|toast| now >= toast.since + SHOW + SLIDE)
+    {
+        state.current = None;
+    }
+    if state.current.is_none() {
+        let text = state.queue.pop_front()?;
+        state.current = Some(Toast { text, since: now });
+    }
+    let since = state.current.as_ref()?.since;
+    if now < since + SHOW {
+        Some(since + SHOW)
+    } else {
+        Some(now + SLIDE_FRAME)
+    }
+}
+
+/// Draws the current toast, call after the views so it stays on top
+pub fn render(display: &mut RenderBuffer, theme: &Theme, now: Instant) {
+    let state = STATE.lock().unwrap();
+    let Some(toast) = &state.current else {
+        return;
+    };
+
+    let bounds = display.bounding_box();
+    let font = font::regular();
+    let max_width = bounds.size.width - 2 * MARGIN as u32;
+    let width = (font.measure(&toast.text) + 2 * PADDING).min(max_width);
+    let x = (bounds.size.width - width) as i32 / 2;
+
+    // Slides down by its height plus the margin once its time is up
+    let sliding = now.saturating_duration_since(toast.since + SHOW);
+    let travel = HEIGHT as i32 + MARGIN;
+    let offset = (travel as u128 * sliding.as_millis() / SLIDE.as_millis()).min(travel as u128);
+    let y = bounds.size.height as i32 - MARGIN - HEIGHT as i32 + offset as i32;
+
+    let area = Rectangle::new(Point::new(x, y), Size::new(width, HEIGHT));
+    RoundedRectangle::with_equal_corners(area, Size::new(6, 6))
+        .into_styled(PrimitiveStyle::with_fill(theme.statusbar))
+        .draw(display)
+        .unwrap();
+    layout::draw_line(
+        display,
+        &toast.text,
+        FontStyle::new(font, theme.statusbar_text),
+        Point::new(x + PADDING as i32, y + 20),
+        width - 2 * PADDING,
+        Align::Center,
+    );
+}
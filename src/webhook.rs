@@ -0,0 +1,637 @@
+//! HTTP webhook receiver letting monitoring systems page the device over
+//! the LAN, enabled by `webhook.conf` in the config directory
+//!
+//! ```text
+//! token = long-random-secret
+//! # Defaults to 8080 on all interfaces
+//! port = 8080
+//! listen = 0.0.0.0
+//! ```
+//!
+//! Every request needs the token as `Authorization: Bearer <token>`, never
+//! in the URL where proxies and access logs would keep it. Endpoints, all
+//! `POST`:
+//!
+//! - `/messages` takes a payload as read by `inbox::parse_payload`, a
+//!   `?priority=` parameter sets the priority when the payload has none
+//! - `/alertmanager` takes Prometheus Alertmanager notifications
+//! - `/grafana` takes Grafana alerting notifications, both the current
+//!   format and the legacy one
+//!
+//! Alert priorities come from the `priority` or `severity` label. Resolved
+//! alerts resolve the message their firing left, matched by fingerprint,
+//! and resends of an alert that is still firing, with the same `startsAt`,
+//! are ignored.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config;
+use crate::inbox::{self, Incoming};
+use crate::json::{self, Value};
+use crate::platform::Priority;
+
+pub const FILE: &str = "webhook.conf";
+
+const DEFAULT_LISTEN: &str = "0.0.0.0";
+const DEFAULT_PORT: u16 = 8080;
+
+/// Largest body accepted, Alertmanager groups stay well under this
+const MAX_BODY: usize = 256 * 1024;
+const MAX_HEADER_LINE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 64;
+/// Time a client gets to send its whole request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Requests served at once, each takes a thread
+const MAX_CONNECTIONS: usize = 4;
+
+static CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+struct Config {
+    listen: String,
+    port: u16,
+    token: String,
+}
+
+impl Config {
+    fn load() -> Option<Self> {
+        let path = config::path(FILE);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                eprintln!("Error reading {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        let mut config = Config {
+            listen: DEFAULT_LISTEN.to_string(),
+            port: DEFAULT_PORT,
+            token: String::new(),
+        };
+        for (key, value) in config::parse(&text) {
+            match key.as_str() {
+                "listen" => config.listen = value,
+                "port" => match value.parse() {
+                    Ok(port) => config.port = port,
+                    Err(_) => {
+                        eprintln!("Error in {}: bad port {:?}", path.display(), value);
+                        return None;
+                    }
+                },
+                "token" => config.token = value,
+                _ => eprintln!("Ignoring unknown webhook setting {}", key),
+            }
+        }
+        // Anyone on the network could page the device otherwise
+        if config.token.is_empty() {
+            eprintln!("Error in {}: no token, webhooks stay off", path.display());
+            return None;
+        }
+        Some(config)
+    }
+}
+
+/// Listens in the background when `webhook.conf` is there
+pub fn start() {
+    let Some(config) = Config::load() else {
+        return;
+    };
+    let address = format!("{}:{}", config.listen, config.port);
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Error opening webhook port {}: {}", address, e);
+            return;
+        }
+    };
+    let token: Arc<str> = config.token.into();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if CONNECTIONS.fetch_add(1, Ordering::Relaxed) >= MAX_CONNECTIONS {
+                CONNECTIONS.fetch_sub(1, Ordering::Relaxed);
+                let _ = respond(stream, "503 Service Unavailable", "busy, try again");
+                continue;
+            }
+            let token = token.clone();
+            thread::spawn(move || {
+                let result = serve(stream, &token);
+                CONNECTIONS.fetch_sub(1, Ordering::Relaxed);
+                if let Err(e) = result {
+                    eprintln!("Webhook connection error: {}", e);
+                }
+            });
+        }
+    });
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    authorization: Option<String>,
+    body: String,
+}
+
+impl Request {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn authorized(&self, token: &str) -> bool {
+        let given = self
+            .authorization
+            .as_deref()
+            .and_then(|value| value.strip_prefix("Bearer "));
+        given.is_some_and(|given| same(given.trim().as_bytes(), token.as_bytes()))
+    }
+}
+
+/// Compares without stopping at the first difference, so response times
+/// give nothing away about the token
+fn same(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// One request per connection, answered and closed
+fn serve(stream: TcpStream, token: &str) -> io::Result<()> {
+    let mut reader = BufReader::new(Deadline {
+        stream: stream.try_clone()?,
+        end: Instant::now() + REQUEST_TIMEOUT,
+    });
+    let (status, reply) = match read_request(&mut reader) {
+        Ok(request) => handle(&request, token),
+        Err(Refused(status, reply)) => (status, reply),
+    };
+    respond(stream, status, &reply)
+}
+
+/// Reads that fail once `end` has passed, so a slow client cannot hold a
+/// connection by trickling bytes
+struct Deadline {
+    stream: TcpStream,
+    end: Instant,
+}
+
+impl Read for Deadline {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.end.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+/// A request that gets an error status instead of being handled
+struct Refused(&'static str, String);
+
+impl From<io::Error> for Refused {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => {
+                Refused("408 Request Timeout", "request took too long".into())
+            }
+            _ => Refused("400 Bad Request", e.to_string()),
+        }
+    }
+}
+
+fn read_request(reader: &mut impl BufRead) -> Result<Request, Refused> {
+    let line = read_line(reader)?;
+    let mut words = line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (words.next(), words.next(), words.next())
+    else {
+        return Err(Refused("400 Bad Request", "bad request line".into()));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (decode(key), decode(value))
+            })
+            .collect(),
+        authorization: None,
+        body: String::new(),
+    };
+
+    let mut length = 0;
+    for count in 0.. {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        if count == MAX_HEADERS {
+            return Err(Refused(
+                "431 Request Header Fields Too Large",
+                "too many headers".into(),
+            ));
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => {
+                length = value
+                    .parse()
+                    .map_err(|_| Refused("400 Bad Request", "bad Content-Length".into()))?
+            }
+            "transfer-encoding" if !value.eq_ignore_ascii_case("identity") => {
+                return Err(Refused(
+                    "411 Length Required",
+                    "send a Content-Length".into(),
+                ));
+            }
+            "authorization" => request.authorization = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    if length > MAX_BODY {
+        return Err(Refused("413 Content Too Large", "body too large".into()));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    request.body = String::from_utf8(body)
+        .map_err(|_| Refused("400 Bad Request", "body is not UTF-8".into()))?;
+    Ok(request)
+}
+
+/// Reads a CRLF or LF terminated line without it
+fn read_line(reader: &mut impl BufRead) -> Result<String, Refused> {
+    let mut line = String::new();
+    reader
+        .by_ref()
+        .take(MAX_HEADER_LINE as u64)
+        .read_line(&mut line)?;
+    if !line.ends_with('\n') {
+        return Err(Refused(
+            "400 Bad Request",
+            "line too long or cut off".into(),
+        ));
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Undoes `%xx` and `+` escapes in a query string
+fn decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' if rest.len() >= 2 => {
+                match std::str::from_utf8(&rest[..2])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(decoded) => {
+                        bytes.push(decoded);
+                        rest = &rest[2..];
+                    }
+                    None => bytes.push(byte),
+                }
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Turns a request body into messages
+type Adapter = fn(&Request) -> Result<Vec<Incoming>, String>;
+
+fn handle(request: &Request, token: &str) -> (&'static str, String) {
+    if !request.authorized(token) {
+        return ("401 Unauthorized", "bad or missing token".into());
+    }
+    let adapter: Adapter = match request.path.as_str() {
+        "/messages" => message,
+        "/alertmanager" | "/grafana" => alerts,
+        _ => return ("404 Not Found", "no such endpoint".into()),
+    };
+    if request.method != "POST" {
+        return ("405 Method Not Allowed", "use POST".into());
+    }
+    match adapter(request) {
+        Ok(messages) => {
+            let count = messages.len();
+            for incoming in messages {
+                inbox::deliver(incoming);
+            }
+            ("200 OK", format!("accepted {}", count))
+        }
+        Err(e) => ("400 Bad Request", e),
+    }
+}
+
+fn message(request: &Request) -> Result<Vec<Incoming>, String> {
+    let mut incoming = inbox::parse_payload(&request.body).ok_or("no subject")?;
+    if incoming.priority.is_none()
+        && let Some(name) = request.param("priority")
+    {
+        let priority = Priority::from_name(name).ok_or(format!("unknown priority {:?}", name))?;
+        incoming.priority = Some(priority);
+    }
+    Ok(vec![incoming])
+}
+
+/// Alertmanager and current Grafana notifications share an `alerts` list,
+/// legacy Grafana ones are a single alert with `title` and `state`
+fn alerts(request: &Request) -> Result<Vec<Incoming>, String> {
+    let payload = json::parse(&request.body).map_err(|e| e.to_string())?;
+    if let Some(alerts) = payload.get("alerts").and_then(Value::as_array) {
+        return Ok(alerts.iter().filter_map(alert).collect());
+    }
+    let field = |key: &str| payload.get(key).and_then(Value::as_str);
+    let title = field("title")
+        .or_else(|| field("ruleName"))
+        .ok_or("no alerts in payload")?;
+    let subject = match field("message") {
+        Some(message) if !message.is_empty() => format!("{}: {}", title, message),
+        _ => title.to_string(),
+    };
+    let mut incoming = Incoming::new(&subject, None);
+    // The title changes with the state, the rule does not
+    incoming.fingerprint = field("ruleName").map(|rule| format!("grafana:{}", rule));
+    incoming.resolved = field("state") == Some("ok");
+    Ok(vec![incoming])
+}
+
+/// One entry of an `alerts` list, summary first, then the alert name
+fn alert(alert: &Value) -> Option<Incoming> {
+    let label = |key: &str| alert.get("labels")?.get(key)?.as_str();
+    let annotation = |key: &str| alert.get("annotations")?.get(key)?.as_str();
+    let subject = annotation("summary")
+        .or_else(|| label("alertname"))
+        .or_else(|| annotation("description"))?;
+    let subject = match label("instance") {
+        Some(instance) if !subject.contains(instance) => format!("{} ({})", subject, instance),
+        _ => subject.to_string(),
+    };
+    let priority = label("priority")
+        .or_else(|| label("severity"))
+        .and_then(severity);
+    let mut incoming = Incoming::new(&subject, priority);
+    incoming.fingerprint = alert
+        .get("fingerprint")
+        .and_then(Value::as_str)
+        .map(String::from);
+    incoming.resolved = alert.get("status").and_then(Value::as_str) == Some("resolved");
+    incoming.starts_at = alert
+        .get("startsAt")
+        .and_then(Value::as_str)
+        .map(String::from);
+    Some(incoming)
+}
+
+/// Priority for the usual severity label values
+fn severity(name: &str) -> Option<Priority> {
+    Priority::from_name(name).or(match name.to_ascii_lowercase().as_str() {
+        "page" | "error" | "major" => Some(Priority::High),
+        "warning" | "warn" | "minor" => Some(Priority::Normal),
+        "info" | "none" => Some(Priority::Low),
+        _ => None,
+    })
+}
+
+fn respond(mut stream: TcpStream, status: &str, reply: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}\n",
+        status,
+        reply.len() + 1,
+        reply
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(text: &str) -> Result<Request, Refused> {
+        read_request(&mut text.as_bytes())
+    }
+
+    fn refused(text: &str) -> &'static str {
+        match request(text) {
+            Ok(_) => panic!("accepted {:?}", text),
+            Err(Refused(status, _)) => status,
+        }
+    }
+
+    #[test]
+    fn reads_request() {
+        let request = request(
+            "POST /messages?priority=high&x=a%20b+c HTTP/1.1\r\n\
+             Host: pager\r\n\
+             authorization: Bearer secret\r\n\
+             Content-Length: 5\r\n\
+             \r\n\
+             hello",
+        )
+        .ok()
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/messages");
+        assert_eq!(request.param("priority"), Some("high"));
+        assert_eq!(request.param("x"), Some("a b c"));
+        assert_eq!(request.body, "hello");
+        assert!(request.authorized("secret"));
+        assert!(!request.authorized("secret2"));
+    }
+
+    #[test]
+    fn token_only_in_header() {
+        let request = request("POST /messages?token=secret HTTP/1.1\r\n\r\n")
+            .ok()
+            .unwrap();
+        assert!(!request.authorized("secret"));
+    }
+
+    #[test]
+    fn refuses_bad_requests() {
+        assert_eq!(refused("nonsense\r\n\r\n"), "400 Bad Request");
+        assert_eq!(
+            refused("POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhi"),
+            "400 Bad Request"
+        );
+        assert_eq!(
+            refused("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n"),
+            "411 Length Required"
+        );
+        let length = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        );
+        assert_eq!(refused(&length), "413 Content Too Large");
+        let long = format!(
+            "POST / HTTP/1.1\r\nX: {}\r\n\r\n",
+            "a".repeat(MAX_HEADER_LINE)
+        );
+        assert_eq!(refused(&long), "400 Bad Request");
+    }
+
+    fn post(path: &str, body: &str) -> Request {
+        Request {
+            method: "POST".into(),
+            path: path.into(),
+            query: Vec::new(),
+            authorization: Some("Bearer secret".into()),
+            body: body.into(),
+        }
+    }
+
+    fn find(fingerprint: &str) -> Option<inbox::Message> {
+        inbox::messages()
+            .into_iter()
+            .find(|m| m.fingerprint == fingerprint)
+    }
+
+    const ALERTMANAGER: &str = r#"{
+        "version": "4",
+        "status": "firing",
+        "alerts": [
+            {
+                "status": "firing",
+                "labels": {"alertname": "HighCPU", "instance": "db1:9100", "severity": "critical"},
+                "annotations": {"summary": "CPU above 95%"},
+                "fingerprint": "test-am-cpu"
+            },
+            {
+                "status": "resolved",
+                "labels": {"alertname": "DiskFull", "severity": "warning"},
+                "annotations": {},
+                "fingerprint": "test-am-disk"
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn alertmanager() {
+        let messages = alerts(&post("/alertmanager", ALERTMANAGER)).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].subject, "CPU above 95% (db1:9100)");
+        assert_eq!(messages[0].priority, Some(Priority::Critical));
+        assert_eq!(messages[0].fingerprint.as_deref(), Some("test-am-cpu"));
+        assert!(!messages[0].resolved);
+        assert_eq!(messages[1].subject, "DiskFull");
+        assert_eq!(messages[1].priority, Some(Priority::Normal));
+        assert!(messages[1].resolved);
+    }
+
+    #[test]
+    fn grafana() {
+        let current = r#"{
+            "receiver": "pager",
+            "status": "firing",
+            "title": "[FIRING:1] Latency",
+            "alerts": [{
+                "status": "firing",
+                "labels": {"alertname": "Latency", "priority": "low"},
+                "annotations": {"description": "p99 over 2s"},
+                "fingerprint": "test-grafana-latency"
+            }]
+        }"#;
+        let messages = alerts(&post("/grafana", current)).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].subject, "Latency");
+        assert_eq!(messages[0].priority, Some(Priority::Low));
+
+        let legacy = r#"{
+            "title": "[Alerting] API errors",
+            "ruleName": "API errors",
+            "state": "alerting",
+            "message": "5xx above 1%"
+        }"#;
+        let messages = alerts(&post("/grafana", legacy)).unwrap();
+        assert_eq!(messages[0].subject, "[Alerting] API errors: 5xx above 1%");
+        assert_eq!(
+            messages[0].fingerprint.as_deref(),
+            Some("grafana:API errors")
+        );
+        assert!(!messages[0].resolved);
+
+        let ok = r#"{"title": "[OK] API errors", "ruleName": "API errors", "state": "ok"}"#;
+        let messages = alerts(&post("/grafana", ok)).unwrap();
+        assert_eq!(messages[0].subject, "[OK] API errors");
+        assert!(messages[0].resolved);
+
+        assert!(alerts(&post("/grafana", r#"{"state": "ok"}"#)).is_err());
+        assert!(alerts(&post("/grafana", "not json")).is_err());
+    }
+
+    #[test]
+    fn alerts_reach_the_inbox() {
+        let firing = r#"{"alerts": [{
+            "status": "firing",
+            "labels": {"alertname": "Webhook test", "severity": "page"},
+            "fingerprint": "test-webhook-flow",
+            "startsAt": "2026-10-18T10:00:00Z"
+        }]}"#;
+        let resolved = firing.replace(r#""firing""#, r#""resolved""#);
+
+        let reply = handle(&post("/alertmanager", firing), "secret");
+        assert_eq!(reply, ("200 OK", "accepted 1".to_string()));
+        // Resent while still firing
+        handle(&post("/alertmanager", firing), "secret");
+        let message = find("test-webhook-flow").unwrap();
+        assert_eq!(message.subject, "Webhook test");
+        assert_eq!(message.priority, Priority::High);
+        assert_eq!(message.count, 1);
+        assert!(message.is_pending());
+
+        handle(&post("/alertmanager", &resolved), "secret");
+        let message = find("test-webhook-flow").unwrap();
+        assert_eq!(message.count, 1);
+        assert!(message.resolved.is_some());
+
+        let again = firing.replace("10:00:00", "11:00:00");
+        handle(&post("/alertmanager", &again), "secret");
+        let message = find("test-webhook-flow").unwrap();
+        assert_eq!(message.count, 2);
+        assert!(message.is_pending());
+    }
+
+    #[test]
+    fn endpoints() {
+        let mut request = post("/messages", "Webhook endpoint test");
+        request.query = vec![("priority".into(), "low".into())];
+        assert_eq!(handle(&request, "secret").0, "200 OK");
+        let message = find("webhook endpoint test").unwrap();
+        assert_eq!(message.priority, Priority::Low);
+
+        request.query = vec![("priority".into(), "loud".into())];
+        assert_eq!(handle(&request, "secret").0, "400 Bad Request");
+        assert_eq!(handle(&request, "other").0, "401 Unauthorized");
+        assert_eq!(handle(&post("/nope", ""), "secret").0, "404 Not Found");
+        request.method = "GET".into();
+        assert_eq!(handle(&request, "secret").0, "405 Method Not Allowed");
+    }
+
+    #[test]
+    fn header_limit() {
+        let headers =
+            |count: usize| format!("POST / HTTP/1.1\r\n{}\r\n", "X-Pad: 1\r\n".repeat(count));
+        assert!(request(&headers(MAX_HEADERS)).is_ok());
+        assert_eq!(
+            refused(&headers(MAX_HEADERS + 1)),
+            "431 Request Header Fields Too Large"
+        );
+    }
+}